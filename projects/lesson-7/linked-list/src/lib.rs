@@ -0,0 +1,909 @@
+//! A doubly linked list kept in a key-value store.
+//!
+//! Every list of a `Key` has a head item at `(key, None)`, whose `next` and `prev` are the first and
+//! last values, and one item per value at `(key, Some(value))`. Where the items live is up to the
+//! `ListStorage` implementation: the runtime keeps them in a `StorageMap`, tests in `memory::MemoryStorage`.
+//! The number of values of each list is kept next to the items, so the items keep their original encoding.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Encode, Decode, Codec};
+use rstd::{result, prelude::*, marker::PhantomData};
+
+#[cfg(feature = "std")]
+pub mod memory;
+
+macro_rules! ensure {
+	( $x:expr, $y:expr $(,)? ) => {{
+		if !$x {
+			return Err($y);
+		}
+	}}
+}
+
+/// Where the items of the lists are kept, addressed by the list key and the value,
+/// `None` being the head of the list
+pub trait ListStorage<Key, Value> {
+	/// The item of `value` in the list of `key`
+	fn get(key: &Key, value: Option<Value>) -> Option<LinkedItem<Value>>;
+	/// Write the item of `value` in the list of `key`
+	fn insert(key: &Key, value: Option<Value>, item: LinkedItem<Value>);
+	/// Remove the item of `value` from the list of `key` and return it
+	fn take(key: &Key, value: Option<Value>) -> Option<LinkedItem<Value>>;
+	/// The recorded number of values in the list of `key`, `None` for lists written before lengths were recorded
+	fn len(key: &Key) -> Option<u32>;
+	/// Record the number of values in the list of `key`
+	fn set_len(key: &Key, len: u32);
+
+	/// Whether there is an item for `value` in the list of `key`
+	fn exists(key: &Key, value: Option<Value>) -> bool {
+		Self::get(key, value).is_some()
+	}
+}
+
+/// Where a `SortedLinkedList` reads the sort keys of its values from
+pub trait SortKeyStorage<Value, SortKey> {
+	/// The sort key of `value`
+	fn get(value: &Value) -> Option<SortKey>;
+}
+
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode)]
+pub struct LinkedItem<Value> {
+	pub prev: Option<Value>,
+	pub next: Option<Value>,
+}
+
+/// A broken invariant found while walking a list
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum LinkError<Value> {
+	/// The value is linked from its neighbour but has no item
+	MissingItem(Value),
+	/// The `prev` of the item does not point back at the item before it
+	AsymmetricLink(Value),
+	/// The `prev` of the head does not point at the last item
+	HeadPrevMismatch,
+	/// More items than the given limit were visited, so the links form a cycle
+	Cycle,
+	/// The recorded length does not match the number of items. (recorded, actual)
+	LenMismatch(u32, u32),
+	/// The value of a sorted list goes before the value in front of it
+	OutOfOrder(Value),
+}
+
+pub struct LinkedList<Storage, Key, Value>(PhantomData<(Storage, Key, Value)>);
+
+impl<Storage, Key, Value> LinkedList<Storage, Key, Value> where
+	Value: Codec + Copy + PartialEq,
+	Key: Codec + Clone,
+	Storage: ListStorage<Key, Value>,
+{
+	fn read_head(key: &Key) -> LinkedItem<Value> {
+		Self::read(key, None)
+	}
+
+	fn write_head(account: &Key, item: LinkedItem<Value>) {
+		Self::write(account, None, item);
+	}
+
+	fn read(key: &Key, value: Option<Value>) -> LinkedItem<Value> {
+		Storage::get(key, value).unwrap_or_else(|| LinkedItem {
+			prev: None,
+			next: None,
+		})
+	}
+
+	fn write(key: &Key, value: Option<Value>, item: LinkedItem<Value>) {
+		Storage::insert(key, value, item);
+	}
+
+	/// Link `value` right after `after`, or at the front if `after` is `None`.
+	/// The caller ensures `after` is in the list and `value` is not.
+	fn link_after(key: &Key, after: Option<Value>, value: Value) {
+		let len = Self::len(key);
+
+		let prev = Self::read(key, after);
+		let next_value = prev.next;
+		let new_prev = LinkedItem {
+			prev: prev.prev,
+			next: Some(value),
+		};
+		Self::write(key, after, new_prev);
+
+		let next = Self::read(key, next_value);
+		let new_next = LinkedItem {
+			prev: Some(value),
+			next: next.next,
+		};
+		Self::write(key, next_value, new_next);
+
+		let item = LinkedItem {
+			prev: after,
+			next: next_value,
+		};
+		Self::write(key, Some(value), item);
+
+		Storage::set_len(key, len + 1);
+	}
+
+	/// Take `value` out of the list, joining its neighbours
+	fn unlink(key: &Key, value: Value) -> Option<LinkedItem<Value>> {
+		if !Storage::exists(key, Some(value)) {
+			return None;
+		}
+		let len = Self::len(key);
+		let item = Storage::take(key, Some(value))?;
+
+		let prev = Self::read(key, item.prev);
+		let new_prev = LinkedItem {
+			prev: prev.prev,
+			next: item.next,
+		};
+
+		Self::write(key, item.prev, new_prev);
+
+		let next = Self::read(key, item.next);
+		let new_next = LinkedItem {
+			prev: item.prev,
+			next: next.next,
+		};
+
+		Self::write(key, item.next, new_next);
+
+		Storage::set_len(key, len - 1);
+
+		Some(item)
+	}
+
+	fn ensure_absent(key: &Key, value: Value) -> result::Result<(), &'static str> {
+		ensure!(!Self::contains(key, value), "Value is already in the list");
+		Ok(())
+	}
+
+	fn ensure_present(key: &Key, value: Value) -> result::Result<(), &'static str> {
+		ensure!(Self::contains(key, value), "Value is not in the list");
+		Ok(())
+	}
+
+	/// Add `value` at the tail of the list. Fails if the value is already in the list.
+	pub fn append(key: &Key, value: Value) -> result::Result<(), &'static str> {
+		Self::ensure_absent(key, value)?;
+
+		Self::link_after(key, Self::last(key), value);
+		Ok(())
+	}
+
+	/// Insert `value` right before `anchor`.
+	/// Fails if `anchor` is not in the list or `value` already is.
+	pub fn insert_before(key: &Key, anchor: Value, value: Value) -> result::Result<(), &'static str> {
+		Self::ensure_absent(key, value)?;
+		let item = Storage::get(key, Some(anchor)).ok_or("Anchor is not in the list")?;
+
+		Self::link_after(key, item.prev, value);
+		Ok(())
+	}
+
+	/// Insert `value` right after `anchor`.
+	/// Fails if `anchor` is not in the list or `value` already is.
+	pub fn insert_after(key: &Key, anchor: Value, value: Value) -> result::Result<(), &'static str> {
+		Self::ensure_absent(key, value)?;
+		ensure!(Self::contains(key, anchor), "Anchor is not in the list");
+
+		Self::link_after(key, Some(anchor), value);
+		Ok(())
+	}
+
+	/// Move `value` to the front of the list. Fails if `value` is not in the list.
+	pub fn move_to_front(key: &Key, value: Value) -> result::Result<(), &'static str> {
+		Self::ensure_present(key, value)?;
+
+		Self::unlink(key, value);
+		Self::link_after(key, None, value);
+		Ok(())
+	}
+
+	/// Move `value` right after `anchor`.
+	/// Fails if either is not in the list or they are the same value.
+	pub fn move_after(key: &Key, anchor: Value, value: Value) -> result::Result<(), &'static str> {
+		ensure!(anchor != value, "Cannot move a value after itself");
+		ensure!(Self::contains(key, anchor), "Anchor is not in the list");
+		Self::ensure_present(key, value)?;
+
+		Self::unlink(key, value);
+		Self::link_after(key, Some(anchor), value);
+		Ok(())
+	}
+
+	/// Remove `value` from the list. Fails if `value` is not in the list.
+	pub fn remove(key: &Key, value: Value) -> result::Result<(), &'static str> {
+		Self::unlink(key, value).map(|_| ()).ok_or("Value is not in the list")
+	}
+
+	/// Number of values in the list.
+	/// Counted by walking the list if no length was recorded for it yet.
+	pub fn len(key: &Key) -> u32 {
+		Storage::len(key).unwrap_or_else(|| Self::count(key))
+	}
+
+	fn count(key: &Key) -> u32 {
+		let mut len = 0;
+		let mut current = Self::read_head(key).next;
+		while let Some(value) = current {
+			len += 1;
+			current = Self::read(key, Some(value)).next;
+		}
+		len
+	}
+
+	/// Record the length of a list written before lengths were recorded, walking it once.
+	/// Returns the number of values walked, zero if the length is already recorded.
+	pub fn record_len(key: &Key) -> u32 {
+		if Storage::len(key).is_some() {
+			return 0;
+		}
+		let len = Self::count(key);
+		Storage::set_len(key, len);
+		len
+	}
+
+	/// Remove the head and the recorded length of an empty list.
+	/// Fails if the list still has values.
+	pub fn clear(key: &Key) -> result::Result<(), &'static str> {
+		ensure!(Self::first(key).is_none(), "List is not empty");
+
+		Storage::take(key, None);
+		Storage::set_len(key, 0);
+		Ok(())
+	}
+
+	/// Whether the list has no values
+	pub fn is_empty(key: &Key) -> bool {
+		Self::len(key) == 0
+	}
+
+	/// Whether `value` is in the list
+	pub fn contains(key: &Key, value: Value) -> bool {
+		Storage::exists(key, Some(value))
+	}
+
+	/// The value at the front of the list
+	pub fn first(key: &Key) -> Option<Value> {
+		Self::read_head(key).next
+	}
+
+	/// The value at the tail of the list
+	pub fn last(key: &Key) -> Option<Value> {
+		Self::read_head(key).prev
+	}
+
+	/// Iterate over the values from front to tail. Use `rev` to iterate from the tail.
+	pub fn iter(key: &Key) -> Iter<Storage, Key, Value> {
+		let head = Self::read_head(key);
+		Iter {
+			key: key.clone(),
+			front: head.next,
+			back: head.prev,
+			remaining: Self::len(key),
+			_marker: PhantomData,
+		}
+	}
+
+	/// At most `limit` values following `start_after`, or from the front if `start_after` is `None`.
+	/// Returns nothing if `start_after` is not in the list.
+	pub fn page(key: &Key, start_after: Option<Value>, limit: u32) -> Vec<Value> {
+		let mut current = match start_after {
+			Some(value) => match Storage::get(key, Some(value)) {
+				Some(item) => item.next,
+				None => return Vec::new(),
+			},
+			None => Self::read_head(key).next,
+		};
+
+		let mut values = Vec::new();
+		while let Some(value) = current {
+			if values.len() as u32 >= limit {
+				break;
+			}
+			values.push(value);
+			current = Self::read(key, Some(value)).next;
+		}
+		values
+	}
+
+	/// Walk the list from the head and verify that the links are consistent.
+	/// Returns the values in order together with every discrepancy found.
+	/// `limit` is the maximum number of items the list can hold.
+	pub fn check(key: &Key, limit: usize) -> (Vec<Value>, Vec<LinkError<Value>>) {
+		let head = Self::read_head(key);
+		let mut values = Vec::new();
+		let mut errors = Vec::new();
+
+		let mut prev = None;
+		let mut current = head.next;
+		while let Some(value) = current {
+			if values.len() >= limit {
+				errors.push(LinkError::Cycle);
+				return (values, errors);
+			}
+
+			let item = match Storage::get(key, Some(value)) {
+				Some(item) => item,
+				None => {
+					errors.push(LinkError::MissingItem(value));
+					return (values, errors);
+				}
+			};
+
+			if item.prev != prev {
+				errors.push(LinkError::AsymmetricLink(value));
+			}
+
+			values.push(value);
+			prev = Some(value);
+			current = item.next;
+		}
+
+		if head.prev != prev {
+			errors.push(LinkError::HeadPrevMismatch);
+		}
+
+		if let Some(len) = Storage::len(key) {
+			if len as usize != values.len() {
+				errors.push(LinkError::LenMismatch(len, values.len() as u32));
+			}
+		}
+
+		(values, errors)
+	}
+}
+
+/// Iterator over the values of a list, created by `LinkedList::iter`
+pub struct Iter<Storage, Key, Value> {
+	key: Key,
+	front: Option<Value>,
+	back: Option<Value>,
+	remaining: u32,
+	_marker: PhantomData<Storage>,
+}
+
+impl<Storage, Key, Value> Iterator for Iter<Storage, Key, Value> where
+	Value: Codec + Copy + PartialEq,
+	Key: Codec + Clone,
+	Storage: ListStorage<Key, Value>,
+{
+	type Item = Value;
+
+	fn next(&mut self) -> Option<Value> {
+		if self.remaining == 0 {
+			return None;
+		}
+		let value = self.front?;
+		self.front = LinkedList::<Storage, Key, Value>::read(&self.key, Some(value)).next;
+		self.remaining -= 1;
+		Some(value)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining as usize, Some(self.remaining as usize))
+	}
+}
+
+impl<Storage, Key, Value> DoubleEndedIterator for Iter<Storage, Key, Value> where
+	Value: Codec + Copy + PartialEq,
+	Key: Codec + Clone,
+	Storage: ListStorage<Key, Value>,
+{
+	fn next_back(&mut self) -> Option<Value> {
+		if self.remaining == 0 {
+			return None;
+		}
+		let value = self.back?;
+		self.back = LinkedList::<Storage, Key, Value>::read(&self.key, Some(value)).prev;
+		self.remaining -= 1;
+		Some(value)
+	}
+}
+
+/// Orders the sort keys of a `SortedLinkedList`
+pub trait Compare<SortKey> {
+	/// Whether `a` goes before `b`
+	fn less(a: &SortKey, b: &SortKey) -> bool;
+}
+
+/// Smallest sort key first
+pub struct Ascending;
+
+impl<SortKey: Ord> Compare<SortKey> for Ascending {
+	fn less(a: &SortKey, b: &SortKey) -> bool {
+		a < b
+	}
+}
+
+/// A `LinkedList` kept in the order of the sort keys of its values.
+/// The sort key of each value is read from `SortKeys`, which must be written before the value is inserted.
+/// Values with equal sort keys keep their insertion order.
+pub struct SortedLinkedList<Storage, SortKeys, Key, Value, SortKey, Comparator>(
+	PhantomData<(Storage, SortKeys, Key, Value, SortKey, Comparator)>
+);
+
+impl<Storage, SortKeys, Key, Value, SortKey, Comparator> SortedLinkedList<Storage, SortKeys, Key, Value, SortKey, Comparator> where
+	Value: Codec + Copy + PartialEq,
+	Key: Codec + Clone,
+	SortKey: Codec + Clone,
+	Storage: ListStorage<Key, Value>,
+	SortKeys: SortKeyStorage<Value, SortKey>,
+	Comparator: Compare<SortKey>,
+{
+	/// The value that `value` goes right after for `sort_key`, `None` for the front of the list.
+	/// That is the last value whose sort key is not greater, walking from `hint`, or from the head if `hint`
+	/// is `None`, past at most `max_steps` values. `value` itself is skipped, so its new place can be found
+	/// before its sort key changes.
+	/// Fails if `hint` is `value`, is not in the list or goes after `value`, or if more values must be walked.
+	pub fn position(
+		key: &Key,
+		value: Value,
+		sort_key: &SortKey,
+		hint: Option<Value>,
+		max_steps: u32,
+	) -> result::Result<Option<Value>, &'static str> {
+		let goes_before = |other: &Value| {
+			SortKeys::get(other).map_or(false, |other_key| Comparator::less(sort_key, &other_key))
+		};
+
+		let (mut after, mut current) = match hint {
+			Some(hint) => {
+				ensure!(hint != value, "Hint is the value itself");
+				let item = Storage::get(key, Some(hint)).ok_or("Hint is not in the list")?;
+				ensure!(!goes_before(&hint), "Hint goes after the value");
+				(Some(hint), item.next)
+			}
+			None => (None, LinkedList::<Storage, Key, Value>::first(key)),
+		};
+
+		let mut steps = 0;
+		while let Some(other) = current {
+			if other != value {
+				if goes_before(&other) {
+					break;
+				}
+				after = Some(other);
+			}
+			ensure!(steps < max_steps, "Too many values to walk from the hint");
+			steps += 1;
+			current = LinkedList::<Storage, Key, Value>::read(key, Some(other)).next;
+		}
+
+		Ok(after)
+	}
+
+	/// Insert `value` right after the last value whose sort key is not greater, see `position`.
+	/// Fails if the value has no sort key or is already in the list, or if `position` fails.
+	pub fn insert(key: &Key, value: Value, hint: Option<Value>, max_steps: u32) -> result::Result<(), &'static str> {
+		LinkedList::<Storage, Key, Value>::ensure_absent(key, value)?;
+		let sort_key = SortKeys::get(&value).ok_or("Value has no sort key")?;
+		let after = Self::position(key, value, &sort_key, hint, max_steps)?;
+
+		LinkedList::<Storage, Key, Value>::link_after(key, after, value);
+		Ok(())
+	}
+
+	/// Remove `value` from the list. Fails if `value` is not in the list.
+	pub fn remove(key: &Key, value: Value) -> result::Result<(), &'static str> {
+		LinkedList::<Storage, Key, Value>::remove(key, value)
+	}
+
+	/// Number of values in the list
+	pub fn len(key: &Key) -> u32 {
+		LinkedList::<Storage, Key, Value>::len(key)
+	}
+
+	/// Record the length of a list written before lengths were recorded. See `LinkedList::record_len`.
+	pub fn record_len(key: &Key) -> u32 {
+		LinkedList::<Storage, Key, Value>::record_len(key)
+	}
+
+	/// Whether `value` is in the list
+	pub fn contains(key: &Key, value: Value) -> bool {
+		LinkedList::<Storage, Key, Value>::contains(key, value)
+	}
+
+	/// The value with the lowest sort key
+	pub fn first(key: &Key) -> Option<Value> {
+		LinkedList::<Storage, Key, Value>::first(key)
+	}
+
+	/// Iterate over the values in sort order
+	pub fn iter(key: &Key) -> Iter<Storage, Key, Value> {
+		LinkedList::<Storage, Key, Value>::iter(key)
+	}
+
+	/// At most `limit` values following `start_after` in sort order, see `LinkedList::page`
+	pub fn page(key: &Key, start_after: Option<Value>, limit: u32) -> Vec<Value> {
+		LinkedList::<Storage, Key, Value>::page(key, start_after, limit)
+	}
+
+	/// Walk the list, see `LinkedList::check`. Out of order values are reported as `LinkError::OutOfOrder`.
+	pub fn check(key: &Key, limit: usize) -> (Vec<Value>, Vec<LinkError<Value>>) {
+		let (values, mut errors) = LinkedList::<Storage, Key, Value>::check(key, limit);
+
+		let mut prev_key: Option<SortKey> = None;
+		for value in values.iter() {
+			let sort_key = SortKeys::get(value);
+			if let (Some(prev_key), Some(sort_key)) = (&prev_key, &sort_key) {
+				if Comparator::less(sort_key, prev_key) {
+					errors.push(LinkError::OutOfOrder(*value));
+				}
+			}
+			if sort_key.is_some() {
+				prev_key = sort_key;
+			}
+		}
+
+		(values, errors)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::VecDeque;
+	use codec::Encode;
+	use super::{LinkedList, SortedLinkedList, LinkedItem, LinkError, ListStorage, Ascending};
+	use super::memory::{self, MemoryStorage, MemorySortKeys};
+
+	type Items = MemoryStorage<u64, u32>;
+	type List = LinkedList<Items, u64, u32>;
+	type Prices = MemorySortKeys<u32, u64>;
+	type SortedList = SortedLinkedList<MemoryStorage<(), u32>, Prices, (), u32, u64, Ascending>;
+
+	/// Assert that `$x` fails with `$y` and leaves every item and length untouched
+	macro_rules! assert_noop {
+		( $x:expr , $y:expr ) => {
+			let before = (memory::items(), memory::lens());
+			assert_eq!($x, Err($y));
+			assert_eq!((memory::items(), memory::lens()), before);
+		}
+	}
+
+	macro_rules! assert_ok {
+		( $x:expr ) => {
+			assert_eq!($x, Ok(()));
+		}
+	}
+
+	#[test]
+	fn items_keep_the_storage_map_layout() {
+		assert_ok!(List::append(&7, 1));
+		assert_ok!(List::append(&7, 2));
+
+		// The runtime keeps the items in a `StorageMap<(Key, Option<Value>), LinkedItem<Value>>`
+		// written as `(prev, next)` by earlier runtimes, so the encoded keys and items must not change
+		let mut expected = std::collections::BTreeMap::new();
+		expected.insert((7u64, None::<u32>).encode(), (Some(2u32), Some(1u32)).encode());
+		expected.insert((7u64, Some(1u32)).encode(), (None::<u32>, Some(2u32)).encode());
+		expected.insert((7u64, Some(2u32)).encode(), (Some(1u32), None::<u32>).encode());
+		assert_eq!(memory::items(), expected);
+		assert_eq!(expected[&(7u64, None::<u32>).encode()], vec![1, 2, 0, 0, 0, 1, 1, 0, 0, 0]);
+
+		assert_eq!(Items::get(&7, None), Some(LinkedItem { prev: Some(2), next: Some(1) }));
+		assert_eq!(Items::len(&7), Some(2));
+	}
+
+	#[test]
+	fn lists_without_a_recorded_length_are_counted() {
+		assert_ok!(List::append(&3, 1));
+		assert_ok!(List::append(&3, 2));
+		assert_ok!(List::append(&3, 3));
+		memory::forget_len(&3);
+
+		assert_eq!(Items::len(&3), None);
+		assert_eq!(List::len(&3), 3);
+		assert_eq!(List::iter(&3).collect::<Vec<_>>(), vec![1, 2, 3]);
+		assert_eq!(List::check(&3, 10), (vec![1, 2, 3], vec![]));
+
+		assert_eq!(List::record_len(&3), 3);
+		assert_eq!(Items::len(&3), Some(3));
+		assert_eq!(List::record_len(&3), 0);
+		memory::forget_len(&3);
+
+		// The first change records the length
+		assert_ok!(List::remove(&3, 2));
+		assert_eq!(Items::len(&3), Some(2));
+
+		assert_noop!(List::clear(&3), "List is not empty");
+		assert_ok!(List::remove(&3, 1));
+		assert_ok!(List::remove(&3, 3));
+		assert_ok!(List::clear(&3));
+		assert_eq!(memory::items(), Default::default());
+		assert_eq!(memory::lens(), Default::default());
+	}
+
+	#[test]
+	fn can_be_iterated_and_paged() {
+		for kitty_id in 1..=5 {
+			assert_ok!(List::append(&0, kitty_id));
+		}
+		assert_ok!(List::remove(&0, 3));
+
+		assert_eq!(List::len(&0), 4);
+		assert_eq!(List::first(&0), Some(1));
+		assert_eq!(List::last(&0), Some(5));
+		assert!(List::contains(&0, 4));
+		assert!(!List::contains(&0, 3));
+
+		assert_eq!(List::iter(&0).collect::<Vec<_>>(), vec![1, 2, 4, 5]);
+		assert_eq!(List::iter(&0).rev().collect::<Vec<_>>(), vec![5, 4, 2, 1]);
+		let mut iter = List::iter(&0);
+		assert_eq!(iter.next(), Some(1));
+		assert_eq!(iter.next_back(), Some(5));
+		assert_eq!(iter.collect::<Vec<_>>(), vec![2, 4]);
+
+		assert_eq!(List::page(&0, None, 2), vec![1, 2]);
+		assert_eq!(List::page(&0, Some(2), 2), vec![4, 5]);
+		assert_eq!(List::page(&0, Some(5), 2), vec![]);
+		assert_eq!(List::page(&0, Some(3), 2), vec![]);
+
+		assert_eq!(List::len(&1), 0);
+		assert!(List::is_empty(&1));
+		assert_eq!(List::iter(&1).next(), None);
+	}
+
+	#[test]
+	fn can_be_reordered() {
+		let assert_order = |expected: Vec<u32>| {
+			let (values, errors) = List::check(&0, 10);
+			assert_eq!(errors, vec![]);
+			assert_eq!(values, expected);
+		};
+
+		assert_ok!(List::append(&0, 1));
+		assert_ok!(List::append(&0, 2));
+		assert_ok!(List::append(&0, 3));
+
+		assert_ok!(List::insert_before(&0, 1, 4));
+		assert_order(vec![4, 1, 2, 3]);
+
+		assert_ok!(List::insert_after(&0, 3, 5));
+		assert_order(vec![4, 1, 2, 3, 5]);
+
+		assert_ok!(List::insert_after(&0, 1, 6));
+		assert_order(vec![4, 1, 6, 2, 3, 5]);
+
+		assert_ok!(List::move_to_front(&0, 5));
+		assert_order(vec![5, 4, 1, 6, 2, 3]);
+
+		assert_ok!(List::move_after(&0, 3, 5));
+		assert_order(vec![4, 1, 6, 2, 3, 5]);
+
+		assert_ok!(List::move_after(&0, 4, 2));
+		assert_order(vec![4, 2, 1, 6, 3, 5]);
+
+		// Invalid moves leave the list untouched
+		assert_noop!(List::move_after(&0, 2, 2), "Cannot move a value after itself");
+		assert_noop!(List::move_after(&0, 9, 2), "Anchor is not in the list");
+		assert_noop!(List::move_after(&0, 2, 9), "Value is not in the list");
+		assert_noop!(List::insert_before(&0, 9, 7), "Anchor is not in the list");
+		assert_noop!(List::insert_after(&0, 4, 1), "Value is already in the list");
+		assert_noop!(List::move_to_front(&0, 9), "Value is not in the list");
+		assert_order(vec![4, 2, 1, 6, 3, 5]);
+	}
+
+	/// A xorshift generator, so every generated sequence of list operations can be replayed from its seed
+	struct Rng(u64);
+
+	impl Rng {
+		fn next(&mut self) -> u64 {
+			self.0 ^= self.0 << 13;
+			self.0 ^= self.0 >> 7;
+			self.0 ^= self.0 << 17;
+			self.0
+		}
+
+		fn below(&mut self, n: u64) -> u64 {
+			self.next() % n
+		}
+	}
+
+	#[derive(Debug, Clone, Copy)]
+	enum ListOp {
+		Append(u64, u32),
+		Remove(u64, u32),
+		InsertBefore(u64, u32, u32),
+		InsertAfter(u64, u32, u32),
+		MoveToFront(u64, u32),
+		MoveAfter(u64, u32, u32),
+	}
+
+	const LIST_KEYS: u64 = 3;
+	// Few values per key, so duplicates and removal of missing values are frequent
+	const LIST_VALUES: u32 = 6;
+	const LIST_SEEDS: u64 = 200;
+	const LIST_STEPS: usize = 100;
+
+	fn random_list_op(rng: &mut Rng) -> ListOp {
+		let key = rng.below(LIST_KEYS);
+		let value = rng.below(LIST_VALUES as u64) as u32;
+		let anchor = rng.below(LIST_VALUES as u64) as u32;
+		// Bias towards appends so the lists grow
+		match rng.below(10) {
+			0..=3 => ListOp::Append(key, value),
+			4..=5 => ListOp::Remove(key, value),
+			6 => ListOp::InsertBefore(key, anchor, value),
+			7 => ListOp::InsertAfter(key, anchor, value),
+			8 => ListOp::MoveToFront(key, value),
+			_ => ListOp::MoveAfter(key, anchor, value),
+		}
+	}
+
+	fn apply_to_list(op: ListOp) -> Result<(), &'static str> {
+		match op {
+			ListOp::Append(key, value) => List::append(&key, value),
+			ListOp::Remove(key, value) => List::remove(&key, value),
+			ListOp::InsertBefore(key, anchor, value) => List::insert_before(&key, anchor, value),
+			ListOp::InsertAfter(key, anchor, value) => List::insert_after(&key, anchor, value),
+			ListOp::MoveToFront(key, value) => List::move_to_front(&key, value),
+			ListOp::MoveAfter(key, anchor, value) => List::move_after(&key, anchor, value),
+		}
+	}
+
+	fn position(model: &VecDeque<u32>, value: u32) -> Option<usize> {
+		model.iter().position(|v| *v == value)
+	}
+
+	/// Apply `op` to the model, returning whether the operation is valid
+	fn apply_to_model(models: &mut [VecDeque<u32>], op: ListOp) -> bool {
+		match op {
+			ListOp::Append(key, value) => {
+				let model = &mut models[key as usize];
+				if model.contains(&value) {
+					return false;
+				}
+				model.push_back(value);
+			}
+			ListOp::Remove(key, value) => {
+				let model = &mut models[key as usize];
+				match position(model, value) {
+					Some(index) => { model.remove(index); }
+					None => return false,
+				}
+			}
+			ListOp::InsertBefore(key, anchor, value) => {
+				let model = &mut models[key as usize];
+				match (position(model, anchor), position(model, value)) {
+					(Some(index), None) => model.insert(index, value),
+					_ => return false,
+				}
+			}
+			ListOp::InsertAfter(key, anchor, value) => {
+				let model = &mut models[key as usize];
+				match (position(model, anchor), position(model, value)) {
+					(Some(index), None) => model.insert(index + 1, value),
+					_ => return false,
+				}
+			}
+			ListOp::MoveToFront(key, value) => {
+				let model = &mut models[key as usize];
+				match position(model, value) {
+					Some(index) => {
+						model.remove(index);
+						model.push_front(value);
+					}
+					None => return false,
+				}
+			}
+			ListOp::MoveAfter(key, anchor, value) => {
+				let model = &mut models[key as usize];
+				if anchor == value || position(model, anchor).is_none() {
+					return false;
+				}
+				match position(model, value) {
+					Some(index) => {
+						model.remove(index);
+						let anchor_index = position(model, anchor).unwrap();
+						model.insert(anchor_index + 1, value);
+					}
+					None => return false,
+				}
+			}
+		}
+		true
+	}
+
+	fn assert_list_matches_model(key: u64, model: &VecDeque<u32>, context: &str) {
+		let expected = model.iter().cloned().collect::<Vec<_>>();
+		let (values, errors) = List::check(&key, LIST_VALUES as usize);
+		assert_eq!(errors, vec![], "{}: broken links for key {}", context, key);
+		assert_eq!(values, expected, "{}: order for key {}", context, key);
+		assert_eq!(List::iter(&key).collect::<Vec<_>>(), expected, "{}: iter for key {}", context, key);
+		assert_eq!(
+			List::iter(&key).rev().collect::<Vec<_>>(),
+			model.iter().rev().cloned().collect::<Vec<_>>(),
+			"{}: reverse iter for key {}", context, key
+		);
+		assert_eq!(List::len(&key), model.len() as u32, "{}: len for key {}", context, key);
+
+		let head = Items::get(&key, None).map_or((None, None), |head| (head.prev, head.next));
+		assert_eq!(head, (model.back().cloned(), model.front().cloned()), "{}: head for key {}", context, key);
+
+		for value in 0..LIST_VALUES {
+			assert_eq!(
+				Items::exists(&key, Some(value)),
+				model.contains(&value),
+				"{}: item {} for key {}", context, value, key
+			);
+		}
+	}
+
+	#[test]
+	fn list_matches_vec_deque_model() {
+		for seed in 1..=LIST_SEEDS {
+			memory::clear();
+			let mut rng = Rng(seed);
+			let mut models = vec![VecDeque::new(); LIST_KEYS as usize];
+
+			for step in 0..LIST_STEPS {
+				let op = random_list_op(&mut rng);
+				let result = apply_to_list(op);
+				let valid = apply_to_model(&mut models, op);
+
+				let context = format!("seed {} step {} {:?}", seed, step, op);
+				assert_eq!(result.is_ok(), valid, "{}: result {:?}", context, result);
+				for key in 0..LIST_KEYS {
+					assert_list_matches_model(key, &models[key as usize], &context);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn rejects_duplicates_and_missing_values() {
+		assert_ok!(List::append(&0, 1));
+		assert_ok!(List::append(&0, 2));
+
+		assert_noop!(List::append(&0, 1), "Value is already in the list");
+		assert_noop!(List::remove(&0, 3), "Value is not in the list");
+		assert_noop!(List::remove(&1, 1), "Value is not in the list");
+	}
+
+	#[test]
+	fn sorted_list_keeps_sort_key_order() {
+		let insert = |value: u32, price: u64| {
+			Prices::set(&value, price);
+			assert_ok!(SortedList::insert(&(), value, None, 10));
+		};
+		insert(0, 30);
+		insert(1, 10);
+		insert(2, 20);
+		// Equal sort keys keep insertion order
+		insert(3, 10);
+		assert_eq!(SortedList::iter(&()).collect::<Vec<_>>(), vec![1, 3, 2, 0]);
+		assert_eq!(SortedList::first(&()), Some(1));
+		assert_eq!(SortedList::page(&(), Some(3), 2), vec![2, 0]);
+
+		assert_noop!(SortedList::insert(&(), 2, None, 10), "Value is already in the list");
+		assert_noop!(SortedList::insert(&(), 9, None, 10), "Value has no sort key");
+
+		assert_ok!(SortedList::remove(&(), 3));
+		assert_eq!(SortedList::len(&()), 3);
+		assert_eq!(SortedList::check(&(), 10), (vec![1, 2, 0], vec![]));
+
+		// The walk is bounded, a hint starts it further down the list
+		Prices::set(&4, 40);
+		assert_noop!(SortedList::insert(&(), 4, None, 2), "Too many values to walk from the hint");
+		assert_noop!(SortedList::insert(&(), 4, Some(3), 10), "Hint is not in the list");
+		assert_noop!(SortedList::insert(&(), 4, Some(4), 10), "Hint is the value itself");
+		Prices::set(&5, 15);
+		assert_noop!(SortedList::insert(&(), 5, Some(2), 10), "Hint goes after the value");
+		assert_ok!(SortedList::insert(&(), 4, Some(2), 1));
+		assert_eq!(SortedList::iter(&()).collect::<Vec<_>>(), vec![1, 2, 0, 4]);
+
+		// The new place of a listed value skips the value itself
+		assert_eq!(SortedList::position(&(), 2, &35, None, 10), Ok(Some(0)));
+		assert_eq!(SortedList::position(&(), 2, &5, None, 10), Ok(None));
+		assert_ok!(SortedList::remove(&(), 4));
+
+		// Changing a sort key without reinserting the value is reported
+		Prices::set(&1, 25);
+		assert_eq!(SortedList::check(&(), 10), (vec![1, 2, 0], vec![LinkError::OutOfOrder(2)]));
+	}
+}
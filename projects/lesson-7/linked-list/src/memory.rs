@@ -0,0 +1,99 @@
+//! In-memory storage for running lists outside of a runtime.
+//!
+//! Items are kept SCALE encoded in thread-local `BTreeMap`s under the encoded `(key, value)`,
+//! the same bytes a runtime `StorageMap` keyed by `(Key, Option<Value>)` hashes into its storage key.
+//! The lengths are kept under the encoded key, zero lengths are removed.
+//! All lists of a thread share the maps, use `clear` to start over.
+
+use std::{cell::RefCell, collections::BTreeMap, marker::PhantomData};
+use codec::{Encode, Decode};
+use crate::{ListStorage, SortKeyStorage, LinkedItem};
+
+thread_local! {
+	static ITEMS: RefCell<BTreeMap<Vec<u8>, Vec<u8>>> = RefCell::new(BTreeMap::new());
+	static LENS: RefCell<BTreeMap<Vec<u8>, u32>> = RefCell::new(BTreeMap::new());
+	static SORT_KEYS: RefCell<BTreeMap<Vec<u8>, Vec<u8>>> = RefCell::new(BTreeMap::new());
+}
+
+fn decode<T: Decode>(bytes: &[u8]) -> T {
+	Decode::decode(&mut &bytes[..]).expect("only encoded values are stored; qed")
+}
+
+/// Remove every item, length and sort key of the current thread
+pub fn clear() {
+	ITEMS.with(|items| items.borrow_mut().clear());
+	LENS.with(|lens| lens.borrow_mut().clear());
+	SORT_KEYS.with(|sort_keys| sort_keys.borrow_mut().clear());
+}
+
+/// The encoded items of the current thread, by encoded `(key, value)`
+pub fn items() -> BTreeMap<Vec<u8>, Vec<u8>> {
+	ITEMS.with(|items| items.borrow().clone())
+}
+
+/// The recorded list lengths of the current thread, by encoded key
+pub fn lens() -> BTreeMap<Vec<u8>, u32> {
+	LENS.with(|lens| lens.borrow().clone())
+}
+
+/// Forget the recorded length of the list of `key`, like a list written before lengths were recorded
+pub fn forget_len<Key: Encode>(key: &Key) {
+	LENS.with(|lens| lens.borrow_mut().remove(&key.encode()));
+}
+
+/// `ListStorage` over the thread-local item map
+pub struct MemoryStorage<Key, Value>(PhantomData<(Key, Value)>);
+
+impl<Key: Encode, Value: Encode + Decode> ListStorage<Key, Value> for MemoryStorage<Key, Value> {
+	fn get(key: &Key, value: Option<Value>) -> Option<LinkedItem<Value>> {
+		let index = (key, value).encode();
+		ITEMS.with(|items| items.borrow().get(&index).map(|bytes| decode(bytes)))
+	}
+
+	fn insert(key: &Key, value: Option<Value>, item: LinkedItem<Value>) {
+		let index = (key, value).encode();
+		ITEMS.with(|items| items.borrow_mut().insert(index, item.encode()));
+	}
+
+	fn take(key: &Key, value: Option<Value>) -> Option<LinkedItem<Value>> {
+		let index = (key, value).encode();
+		ITEMS.with(|items| items.borrow_mut().remove(&index).map(|bytes| decode(&bytes)))
+	}
+
+	fn len(key: &Key) -> Option<u32> {
+		LENS.with(|lens| lens.borrow().get(&key.encode()).cloned())
+	}
+
+	fn set_len(key: &Key, len: u32) {
+		LENS.with(|lens| match len {
+			0 => lens.borrow_mut().remove(&key.encode()),
+			len => lens.borrow_mut().insert(key.encode(), len),
+		});
+	}
+
+	fn exists(key: &Key, value: Option<Value>) -> bool {
+		let index = (key, value).encode();
+		ITEMS.with(|items| items.borrow().contains_key(&index))
+	}
+}
+
+/// `SortKeyStorage` over the thread-local sort key map
+pub struct MemorySortKeys<Value, SortKey>(PhantomData<(Value, SortKey)>);
+
+impl<Value: Encode, SortKey: Encode> MemorySortKeys<Value, SortKey> {
+	/// Set the sort key of `value`
+	pub fn set(value: &Value, sort_key: SortKey) {
+		SORT_KEYS.with(|sort_keys| sort_keys.borrow_mut().insert(value.encode(), sort_key.encode()));
+	}
+
+	/// Remove the sort key of `value`
+	pub fn remove(value: &Value) {
+		SORT_KEYS.with(|sort_keys| sort_keys.borrow_mut().remove(&value.encode()));
+	}
+}
+
+impl<Value: Encode, SortKey: Decode> SortKeyStorage<Value, SortKey> for MemorySortKeys<Value, SortKey> {
+	fn get(value: &Value) -> Option<SortKey> {
+		SORT_KEYS.with(|sort_keys| sort_keys.borrow().get(&value.encode()).map(|bytes| decode(bytes)))
+	}
+}
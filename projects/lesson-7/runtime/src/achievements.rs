@@ -0,0 +1,205 @@
+//! Achievements for raising and trading kitties, unlocked through the kitties hooks
+
+use support::{decl_module, decl_storage, decl_event, StorageMap};
+use sr_primitives::traits::Saturating;
+use codec::{Encode, Decode};
+use rstd::prelude::*;
+use crate::kitties::{OnKittyCreated, OnKittyTransferred};
+
+pub trait Trait: system::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+}
+
+/// Number of kitties an account must raise to become a breeder
+pub const BREEDER_KITTIES: u32 = 10;
+
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+	/// Got a kitty, whether created, hatched, bought or given
+	FirstKitty,
+	/// Raised `BREEDER_KITTIES` kitties
+	Breeder,
+	/// Sold a kitty
+	FirstSale,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Achievements {
+		/// Number of kitties created or hatched for an account
+		pub KittiesRaised get(kitties_raised): map T::AccountId => u32;
+		/// Number of kitties an account sold
+		pub KittiesSold get(kitties_sold): map T::AccountId => u32;
+		/// Get the achievements of an account, in the order they were unlocked
+		pub Unlocked get(achievements): map T::AccountId => Vec<Achievement>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where AccountId = <T as system::Trait>::AccountId {
+		/// An account unlocked an achievement. (who, achievement)
+		Achieved(AccountId, Achievement),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event<T>() = default;
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Unlock an achievement, unless `who` already has it
+	fn achieve(who: &T::AccountId, achievement: Achievement) {
+		if Self::achievements(who).contains(&achievement) {
+			return;
+		}
+
+		<Unlocked<T>>::mutate(who, |achievements| achievements.push(achievement));
+
+		Self::deposit_event(RawEvent::Achieved(who.clone(), achievement));
+	}
+}
+
+impl<T: Trait, KittyIndex> OnKittyCreated<T::AccountId, KittyIndex> for Module<T> {
+	fn on_kitty_created(owner: Option<&T::AccountId>, _: KittyIndex) {
+		// Kitties hatched into the shelter are raised by no one
+		let owner = match owner {
+			Some(owner) => owner,
+			None => return,
+		};
+		let raised = Self::kitties_raised(owner).saturating_add(1);
+		<KittiesRaised<T>>::insert(owner, raised);
+
+		Self::achieve(owner, Achievement::FirstKitty);
+		if raised >= BREEDER_KITTIES {
+			Self::achieve(owner, Achievement::Breeder);
+		}
+	}
+}
+
+impl<T: Trait, KittyIndex, Balance> OnKittyTransferred<T::AccountId, KittyIndex, Balance> for Module<T> {
+	fn on_kitty_transferred(from: Option<&T::AccountId>, to: &T::AccountId, _: KittyIndex, price: Option<Balance>) {
+		Self::achieve(to, Achievement::FirstKitty);
+		if let (Some(from), Some(_)) = (from, price) {
+			<KittiesSold<T>>::mutate(from, |sold| *sold = sold.saturating_add(1));
+			Self::achieve(from, Achievement::FirstSale);
+		}
+	}
+}
+
+/// Tests for Achievements module
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use primitives::{H256, Blake2Hasher};
+	use support::{impl_outer_origin, impl_outer_event, parameter_types};
+	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+	use sr_primitives::weights::Weight;
+	use sr_primitives::Perbill;
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	mod achievements {
+		pub use crate::achievements::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for Test {
+			achievements<T>,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Call = ();
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type WeightMultiplierUpdate = ();
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	impl Trait for Test {
+		type Event = TestEvent;
+	}
+	type Achievements = Module<Test>;
+	type System = system::Module<Test>;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		let mut ext: runtime_io::TestExternalities<Blake2Hasher> = t.into();
+		// Events are not recorded in the genesis block
+		with_externalities(&mut ext, || System::set_block_number(1));
+		ext
+	}
+
+	fn created(owner: Option<u64>, kitty_id: u32) {
+		<Achievements as OnKittyCreated<u64, u32>>::on_kitty_created(owner.as_ref(), kitty_id);
+	}
+
+	fn transferred(from: Option<u64>, to: u64, kitty_id: u32, price: Option<u64>) {
+		<Achievements as OnKittyTransferred<u64, u32, u64>>::on_kitty_transferred(from.as_ref(), &to, kitty_id, price);
+	}
+
+	#[test]
+	fn raising_kitties_unlocks_achievements_once() {
+		with_externalities(&mut new_test_ext(), || {
+			created(Some(1), 0);
+			assert_eq!(Achievements::achievements(1), vec![Achievement::FirstKitty]);
+			assert_eq!(
+				System::events().pop().map(|e| e.event),
+				Some(TestEvent::achievements(RawEvent::Achieved(1, Achievement::FirstKitty)))
+			);
+
+			for kitty_id in 1..BREEDER_KITTIES {
+				created(Some(1), kitty_id);
+			}
+			assert_eq!(Achievements::kitties_raised(1), BREEDER_KITTIES);
+			assert_eq!(Achievements::achievements(1), vec![Achievement::FirstKitty, Achievement::Breeder]);
+
+			// Kitties hatched into the shelter count for no one
+			created(None, BREEDER_KITTIES);
+			assert_eq!(Achievements::kitties_raised(1), BREEDER_KITTIES);
+			assert_eq!(System::events().len(), 2);
+		});
+	}
+
+	#[test]
+	fn selling_a_kitty_unlocks_first_sale() {
+		with_externalities(&mut new_test_ext(), || {
+			transferred(Some(1), 2, 0, None);
+			assert_eq!(Achievements::achievements(1), vec![]);
+			assert_eq!(Achievements::achievements(2), vec![Achievement::FirstKitty]);
+
+			transferred(Some(2), 3, 0, Some(10));
+			assert_eq!(Achievements::kitties_sold(2), 1);
+			assert_eq!(Achievements::achievements(2), vec![Achievement::FirstKitty, Achievement::FirstSale]);
+			assert_eq!(Achievements::achievements(3), vec![Achievement::FirstKitty]);
+
+			// Adopting a kitty is not a sale
+			transferred(None, 4, 1, None);
+			assert_eq!(Achievements::achievements(4), vec![Achievement::FirstKitty]);
+		});
+	}
+}
@@ -0,0 +1,43 @@
+//! Runtime APIs of the kitties runtime
+
+use rstd::prelude::*;
+use client::decl_runtime_apis;
+use crate::kitties::{IntegrityError, EggId, LifeStage, Appearance};
+
+decl_runtime_apis! {
+	/// Queries of the kitties module
+	pub trait KittiesApi<AccountId, KittyIndex, Balance> where
+		AccountId: codec::Codec,
+		KittyIndex: codec::Codec,
+		Balance: codec::Codec,
+	{
+		/// Number of kitties owned by `owner`
+		fn kitty_count_of(owner: AccountId) -> u32;
+		/// Kitties of `owner` following `start_after`, at most `limit` and never more than `kitties::MAX_KITTIES_PAGE`
+		fn kitties_of(owner: AccountId, start_after: Option<KittyIndex>, limit: u32) -> Vec<KittyIndex>;
+		/// Kitties for sale with their prices, cheapest first, following `start_after`.
+		/// At most `limit` and never more than `kitties::MAX_KITTIES_PAGE`.
+		fn listings(start_after: Option<KittyIndex>, limit: u32) -> Vec<(KittyIndex, Balance)>;
+		/// The fee to breed `kitty_id_1` with `kitty_id_2` in the current block
+		fn breeding_fee(kitty_id_1: KittyIndex, kitty_id_2: KittyIndex) -> Balance;
+		/// The fee to breed a kitty whose older parent is of `generation`, at the current supply
+		fn breeding_fee_for(generation: u32) -> Balance;
+		/// Eggs of `owner` waiting to hatch
+		fn eggs_of(owner: AccountId) -> Vec<EggId>;
+		/// The stage of life of a kitty in the current block
+		fn life_stage(kitty_id: KittyIndex) -> Option<LifeStage>;
+		/// How the DNA of a kitty shows at its current stage of life
+		fn appearance(kitty_id: KittyIndex) -> Option<Appearance>;
+	}
+
+	/// Debugging helpers for the kitties module.
+	/// These walk the whole kitties storage and are not meant for regular clients.
+	/// Only implemented by runtimes built with the `debug-api` feature.
+	pub trait KittiesDebugApi<AccountId, KittyIndex> where
+		AccountId: codec::Codec,
+		KittyIndex: codec::Codec,
+	{
+		/// Every discrepancy between the kitties storage items
+		fn check_integrity() -> Vec<IntegrityError<AccountId, KittyIndex>>;
+	}
+}
@@ -0,0 +1,715 @@
+use support::{
+	decl_module, decl_storage, decl_event, ensure, StorageValue, StorageMap,
+	traits::{Currency, ReservableCurrency, Get},
+};
+use sr_primitives::traits::{Zero, Saturating};
+use codec::{Encode, Decode};
+use runtime_io::blake2_128;
+use system::ensure_signed;
+use rstd::prelude::*;
+use crate::nft::{NonFungibleAsset, AssetDna};
+
+pub trait Trait: system::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// Pays tournament entry fees
+	type Currency: ReservableCurrency<Self::AccountId>;
+	/// The fighters
+	type Nft: AssetDna<Self::AccountId>;
+	/// Largest number of entrants of a tournament
+	type MaxTournamentEntrants: Get<u32>;
+	/// Reserved from the organiser until the tournament is run
+	type TournamentDeposit: Get<BalanceOf<Self>>;
+	/// Largest number of tournaments run at one block
+	type MaxTournamentsPerBlock: Get<u32>;
+}
+
+pub type TournamentId = u64;
+
+/// Rating of a kitty that never fought
+pub const INITIAL_RATING: u32 = 1200;
+/// Largest rating change of a fight
+pub const K_FACTOR: u32 = 32;
+
+/// Expected score of the higher rated fighter in per mille, by rating difference in steps of 25.
+/// Differences above 800 count as 800.
+const EXPECTED_SCORES: [u32; 33] = [
+	500, 536, 571, 606, 640, 673, 703, 733, 760, 785, 808, 830, 849, 867, 882, 896, 909,
+	920, 930, 939, 947, 954, 960, 965, 969, 973, 977, 980, 983, 985, 987, 989, 990,
+];
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type AssetIdOf<T> = <<T as Trait>::Nft as NonFungibleAsset<<T as system::Trait>::AccountId>>::AssetId;
+type TournamentOf<T> = Tournament<
+	<T as system::Trait>::AccountId,
+	AssetIdOf<T>,
+	BalanceOf<T>,
+	<T as system::Trait>::BlockNumber,
+>;
+
+/// Fighting traits decoded from the DNA
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+pub struct Traits {
+	pub attack: u8,
+	pub defence: u8,
+	pub speed: u8,
+}
+
+impl Traits {
+	pub fn from_dna(dna: &[u8; 16]) -> Self {
+		Traits {
+			attack: dna[0],
+			defence: dna[1],
+			speed: dna[2],
+		}
+	}
+
+	/// Strength of the kitty before the dice roll
+	pub fn power(&self) -> u32 {
+		self.attack as u32 + self.defence as u32 + self.speed as u32
+	}
+}
+
+/// A knockout tournament run at its start block
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode, Clone)]
+pub struct Tournament<AccountId, AssetId, Balance, BlockNumber> {
+	pub organiser: AccountId,
+	/// Reserved from the organiser, returned when the tournament is run
+	pub deposit: Balance,
+	/// Reserved from each entrant. The champion takes all the fees.
+	pub entry_fee: Balance,
+	pub start: BlockNumber,
+	pub max_entrants: u32,
+	/// The bracket, in entry order
+	pub entrants: Vec<(AccountId, AssetId)>,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Battle {
+		/// Ratings of the kitties that fought. Use `rating` to get the rating of any kitty.
+		pub Ratings: map AssetIdOf<T> => Option<u32>;
+		/// Get the open challenge of a kitty, by challenger kitty. (challenger, opponent kitty)
+		pub Challenges get(open_challenge): map AssetIdOf<T> => Option<(T::AccountId, AssetIdOf<T>)>;
+		/// Get tournament. Tournaments stay here until they are run.
+		pub Tournaments get(tournament): map TournamentId => Option<TournamentOf<T>>;
+		/// The id of the next tournament
+		pub NextTournamentId get(next_tournament_id): TournamentId;
+		/// Get the tournaments run at a block
+		pub TournamentsAt get(tournaments_at): map T::BlockNumber => Vec<TournamentId>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::BlockNumber,
+		AssetId = AssetIdOf<T>,
+		Balance = BalanceOf<T>,
+	{
+		/// A kitty challenges another. (challenger, challenger kitty, opponent kitty)
+		Challenged(AccountId, AssetId, AssetId),
+		/// A challenge is withdrawn. (challenger kitty)
+		ChallengeCancelled(AssetId),
+		/// A fight is over. (winner, loser, winner rating, loser rating)
+		Fought(AssetId, AssetId, u32, u32),
+		/// A tournament is scheduled. (tournament_id, organiser, entry_fee, start)
+		TournamentCreated(TournamentId, AccountId, Balance, BlockNumber),
+		/// A kitty enters a tournament. (tournament_id, owner, kitty)
+		Entered(TournamentId, AccountId, AssetId),
+		/// A round of a tournament is played. (tournament_id, round, kitties going through)
+		RoundPlayed(TournamentId, u32, Vec<AssetId>),
+		/// A tournament is won. (tournament_id, owner, kitty, prize)
+		TournamentWon(TournamentId, AccountId, AssetId, Balance),
+		/// A tournament did not have enough kitties and its fees are refunded. (tournament_id)
+		TournamentCancelled(TournamentId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		fn on_initialize(now: T::BlockNumber) {
+			for tournament_id in <TournamentsAt<T>>::take(now) {
+				Self::run_tournament(tournament_id);
+			}
+		}
+
+		/// Challenge `opponent` with `kitty_id`. Its owner fights by accepting the challenge.
+		pub fn challenge(origin, kitty_id: AssetIdOf<T>, opponent: AssetIdOf<T>) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(T::Nft::owner_of(kitty_id) == Some(sender.clone()), "Only owner can challenge");
+			let opponent_owner = T::Nft::owner_of(opponent).ok_or("Opponent does not exist")?;
+			ensure!(opponent_owner != sender, "Cannot challenge own kitty");
+			ensure!(T::Nft::can_play(kitty_id), "Kitty cannot fight");
+			ensure!(T::Nft::can_play(opponent), "Opponent cannot fight");
+
+			<Challenges<T>>::insert(kitty_id, (sender.clone(), opponent));
+
+			Self::deposit_event(RawEvent::Challenged(sender, kitty_id, opponent));
+		}
+
+		/// Withdraw the challenge of `kitty_id`
+		pub fn cancel_challenge(origin, kitty_id: AssetIdOf<T>) {
+			let sender = ensure_signed(origin)?;
+
+			let (challenger, _) = Self::open_challenge(kitty_id).ok_or("No challenge")?;
+			ensure!(challenger == sender, "Only challenger can cancel challenge");
+
+			<Challenges<T>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::ChallengeCancelled(kitty_id));
+		}
+
+		/// Accept the challenge of `challenger` to `kitty_id` and fight it
+		pub fn accept_challenge(origin, challenger: AssetIdOf<T>, kitty_id: AssetIdOf<T>) {
+			let sender = ensure_signed(origin)?;
+
+			let (challenger_owner, opponent) = Self::open_challenge(challenger).ok_or("No challenge")?;
+			ensure!(opponent == kitty_id, "No challenge");
+			ensure!(T::Nft::owner_of(kitty_id) == Some(sender.clone()), "Only owner can accept challenge");
+			ensure!(T::Nft::owner_of(challenger) == Some(challenger_owner), "Challenger kitty has changed owner");
+			ensure!(T::Nft::can_play(kitty_id), "Kitty cannot fight");
+			ensure!(T::Nft::can_play(challenger), "Opponent cannot fight");
+
+			<Challenges<T>>::remove(challenger);
+			Self::fight(challenger, kitty_id, <system::Module<T>>::extrinsic_index());
+		}
+
+		/// Schedule a knockout tournament starting at `start`, reserving the tournament deposit
+		pub fn create_tournament(origin, entry_fee: BalanceOf<T>, max_entrants: u32, start: T::BlockNumber) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(max_entrants >= 2 && max_entrants <= T::MaxTournamentEntrants::get(), "Invalid number of entrants");
+			ensure!(start > <system::Module<T>>::block_number(), "Start must be in the future");
+			ensure!(
+				(Self::tournaments_at(start).len() as u32) < T::MaxTournamentsPerBlock::get(),
+				"Too many tournaments at this block"
+			);
+
+			let tournament_id = Self::next_tournament_id();
+			let next_tournament_id = tournament_id.checked_add(1).ok_or("Tournaments count overflow")?;
+
+			let deposit = T::TournamentDeposit::get();
+			T::Currency::reserve(&sender, deposit)?;
+
+			<Tournaments<T>>::insert(tournament_id, Tournament {
+				organiser: sender.clone(),
+				deposit,
+				entry_fee,
+				start,
+				max_entrants,
+				entrants: Vec::new(),
+			});
+			<TournamentsAt<T>>::mutate(start, |ids| ids.push(tournament_id));
+			NextTournamentId::put(next_tournament_id);
+
+			Self::deposit_event(RawEvent::TournamentCreated(tournament_id, sender, entry_fee, start));
+		}
+
+		/// Enter `kitty_id` in a tournament, reserving the entry fee.
+		/// The kitty forfeits if it changes owner before the tournament starts.
+		pub fn enter_tournament(origin, tournament_id: TournamentId, kitty_id: AssetIdOf<T>) {
+			let sender = ensure_signed(origin)?;
+
+			let mut tournament = Self::tournament(tournament_id).ok_or("Tournament does not exist")?;
+			ensure!(T::Nft::owner_of(kitty_id) == Some(sender.clone()), "Only owner can enter kitty");
+			ensure!(T::Nft::can_play(kitty_id), "Kitty cannot fight");
+			ensure!((tournament.entrants.len() as u32) < tournament.max_entrants, "Tournament is full");
+			ensure!(tournament.entrants.iter().all(|(_, entrant)| *entrant != kitty_id), "Kitty already entered");
+
+			T::Currency::reserve(&sender, tournament.entry_fee)?;
+
+			tournament.entrants.push((sender.clone(), kitty_id));
+			<Tournaments<T>>::insert(tournament_id, tournament);
+
+			Self::deposit_event(RawEvent::Entered(tournament_id, sender, kitty_id));
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The rating of a kitty
+	pub fn rating(kitty_id: AssetIdOf<T>) -> u32 {
+		<Ratings<T>>::get(kitty_id).unwrap_or(INITIAL_RATING)
+	}
+
+	/// Expected score of a fighter rated `rating` against one rated `opponent`, in per mille
+	pub fn expected_score(rating: u32, opponent: u32) -> u32 {
+		let step = |difference: u32| EXPECTED_SCORES[(difference / 25).min(32) as usize];
+		if rating >= opponent {
+			step(rating - opponent)
+		} else {
+			1000 - step(opponent - rating)
+		}
+	}
+
+	/// Ratings of the winner and the loser after a fight
+	pub fn rate(winner: u32, loser: u32) -> (u32, u32) {
+		let expected = Self::expected_score(winner, loser);
+		// Round to nearest
+		let change = (K_FACTOR * (1000 - expected) + 500) / 1000;
+		(winner.saturating_add(change), loser.saturating_sub(change))
+	}
+
+	fn random_value<S: Encode>(subject: S) -> [u8; 16] {
+		let payload = (<system::Module<T>>::random_seed(), subject, <system::Module<T>>::block_number());
+		payload.using_encoded(blake2_128)
+	}
+
+	/// Fight two kitties and update their ratings. `subject` tells fights of the block apart.
+	/// Returns the winner.
+	fn fight<S: Encode>(kitty_1: AssetIdOf<T>, kitty_2: AssetIdOf<T>, subject: S) -> AssetIdOf<T> {
+		let power = |kitty_id: AssetIdOf<T>| T::Nft::dna(kitty_id).map(|dna| Traits::from_dna(&dna).power()).unwrap_or(0);
+		let random = Self::random_value((kitty_1, kitty_2, subject));
+		let strength_1 = power(kitty_1) + random[0] as u32 + random[1] as u32;
+		let strength_2 = power(kitty_2) + random[2] as u32 + random[3] as u32;
+
+		let kitty_1_wins = strength_1 > strength_2 || (strength_1 == strength_2 && random[4] & 1 == 0);
+		let (winner, loser) = if kitty_1_wins { (kitty_1, kitty_2) } else { (kitty_2, kitty_1) };
+
+		let (winner_rating, loser_rating) = Self::rate(Self::rating(winner), Self::rating(loser));
+		<Ratings<T>>::insert(winner, winner_rating);
+		<Ratings<T>>::insert(loser, loser_rating);
+
+		Self::deposit_event(RawEvent::Fought(winner, loser, winner_rating, loser_rating));
+		winner
+	}
+
+	/// Play all rounds of a tournament and pay the fees to the champion.
+	/// Kitties that changed owner or cannot fight anymore forfeit, but their fees still go to the champion.
+	fn run_tournament(tournament_id: TournamentId) {
+		let tournament = match <Tournaments<T>>::take(tournament_id) {
+			Some(tournament) => tournament,
+			None => return,
+		};
+		T::Currency::unreserve(&tournament.organiser, tournament.deposit);
+
+		let mut bracket: Vec<(T::AccountId, AssetIdOf<T>)> = tournament.entrants.iter()
+			.filter(|(owner, kitty_id)| T::Nft::owner_of(*kitty_id).as_ref() == Some(owner) && T::Nft::can_play(*kitty_id))
+			.cloned()
+			.collect();
+
+		if tournament.entrants.len() < 2 || bracket.is_empty() {
+			for (entrant, _) in tournament.entrants.iter() {
+				T::Currency::unreserve(entrant, tournament.entry_fee);
+			}
+			Self::deposit_event(RawEvent::TournamentCancelled(tournament_id));
+			return;
+		}
+
+		let mut round = 0u32;
+		while bracket.len() > 1 {
+			round += 1;
+			bracket = bracket.chunks(2).map(|pair| match pair {
+				[first, second] => {
+					let winner = Self::fight(first.1, second.1, (tournament_id, round));
+					if winner == first.1 { first.clone() } else { second.clone() }
+				},
+				// Odd one out gets a bye
+				_ => pair[0].clone(),
+			}).collect();
+
+			Self::deposit_event(RawEvent::RoundPlayed(tournament_id, round, bracket.iter().map(|(_, kitty_id)| *kitty_id).collect()));
+		}
+
+		let (champion, kitty_id) = bracket.remove(0);
+		let mut prize = BalanceOf::<T>::zero();
+		for (entrant, _) in tournament.entrants.iter() {
+			let unpaid = if *entrant == champion {
+				T::Currency::unreserve(entrant, tournament.entry_fee);
+				Zero::zero()
+			} else {
+				T::Currency::repatriate_reserved(entrant, &champion, tournament.entry_fee).unwrap_or(tournament.entry_fee)
+			};
+			prize = prize.saturating_add(tournament.entry_fee.saturating_sub(unpaid));
+		}
+
+		Self::deposit_event(RawEvent::TournamentWon(tournament_id, champion, kitty_id, prize));
+	}
+}
+
+/// Tests for Battle module
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use primitives::{H256, Blake2Hasher};
+	use support::{impl_outer_origin, impl_outer_event, assert_ok, assert_noop, parameter_types};
+	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup, OnInitialize}, testing::Header};
+	use sr_primitives::weights::Weight;
+	use sr_primitives::Perbill;
+	use rstd::result;
+	use std::{cell::RefCell, collections::{BTreeMap, BTreeSet}};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	mod battle {
+		pub use crate::battle::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for Test {
+			balances<T>,
+			battle<T>,
+		}
+	}
+
+	thread_local! {
+		static KITTIES: RefCell<BTreeMap<u32, (u64, [u8; 16])>> = RefCell::new(BTreeMap::new());
+		static BENCHED: RefCell<BTreeSet<u32>> = RefCell::new(BTreeSet::new());
+	}
+
+	/// Kitties with a chosen DNA, so fights between far apart kitties do not depend on the dice
+	pub struct MockKitties;
+
+	impl MockKitties {
+		fn create(kitty_id: u32, owner: u64, gene: u8) {
+			KITTIES.with(|kitties| kitties.borrow_mut().insert(kitty_id, (owner, [gene; 16])));
+		}
+
+		/// Keep a kitty out of fights, like a frozen or too young kitty
+		fn bench(kitty_id: u32) {
+			BENCHED.with(|benched| benched.borrow_mut().insert(kitty_id));
+		}
+	}
+
+	impl NonFungibleAsset<u64> for MockKitties {
+		type AssetId = u32;
+
+		fn exists(kitty_id: u32) -> bool {
+			KITTIES.with(|kitties| kitties.borrow().contains_key(&kitty_id))
+		}
+
+		fn owner_of(kitty_id: u32) -> Option<u64> {
+			KITTIES.with(|kitties| kitties.borrow().get(&kitty_id).map(|kitty| kitty.0))
+		}
+
+		fn total_supply() -> u64 {
+			KITTIES.with(|kitties| kitties.borrow().len() as u64)
+		}
+
+		fn transfer(from: &u64, to: &u64, kitty_id: u32) -> result::Result<(), &'static str> {
+			KITTIES.with(|kitties| match kitties.borrow_mut().get_mut(&kitty_id) {
+				Some(kitty) if kitty.0 == *from => {
+					kitty.0 = *to;
+					Ok(())
+				},
+				_ => Err("Only owner can transfer kitty"),
+			})
+		}
+
+		fn lock(_: u32) -> result::Result<(), &'static str> {
+			Ok(())
+		}
+
+		fn unlock(_: u32) -> result::Result<(), &'static str> {
+			Ok(())
+		}
+
+		fn is_locked(_: u32) -> bool {
+			false
+		}
+
+		fn can_receive(_: &u64) -> bool {
+			true
+		}
+	}
+
+	impl AssetDna<u64> for MockKitties {
+		fn dna(kitty_id: u32) -> Option<[u8; 16]> {
+			KITTIES.with(|kitties| kitties.borrow().get(&kitty_id).map(|kitty| kitty.1))
+		}
+
+		fn can_play(kitty_id: u32) -> bool {
+			Self::exists(kitty_id) && BENCHED.with(|benched| !benched.borrow().contains(&kitty_id))
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Call = ();
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type WeightMultiplierUpdate = ();
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+		pub const TransactionBaseFee: u64 = 0;
+		pub const TransactionByteFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnNewAccount = ();
+		type Event = TestEvent;
+		type TransactionPayment = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+		type TransactionBaseFee = TransactionBaseFee;
+		type TransactionByteFee = TransactionByteFee;
+		type WeightToFee = ();
+	}
+	parameter_types! {
+		pub const MaxTournamentEntrants: u32 = 4;
+		pub const TournamentDeposit: u64 = 5;
+		pub const MaxTournamentsPerBlock: u32 = 2;
+	}
+	impl Trait for Test {
+		type Event = TestEvent;
+		type Currency = Balances;
+		type Nft = MockKitties;
+		type MaxTournamentEntrants = MaxTournamentEntrants;
+		type TournamentDeposit = TournamentDeposit;
+		type MaxTournamentsPerBlock = MaxTournamentsPerBlock;
+	}
+	type BattleModule = Module<Test>;
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+
+	// Kitty 0 of account 1 beats any weakling whatever the dice.
+	// Kitties 1, 2 and 3 of accounts 2, 3 and 4 are weaklings. Accounts have 100 each.
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![(1, 100), (2, 100), (3, 100), (4, 100), (5, 100)],
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+
+		KITTIES.with(|kitties| kitties.borrow_mut().clear());
+		BENCHED.with(|benched| benched.borrow_mut().clear());
+		MockKitties::create(0, 1, 255);
+		MockKitties::create(1, 2, 0);
+		MockKitties::create(2, 3, 0);
+		MockKitties::create(3, 4, 0);
+
+		let mut ext: runtime_io::TestExternalities<Blake2Hasher> = t.into();
+		with_externalities(&mut ext, || System::set_block_number(1));
+		ext
+	}
+
+	fn last_event() -> TestEvent {
+		System::events().pop().expect("Event expected").event
+	}
+
+	fn events() -> Vec<TestEvent> {
+		System::events().into_iter().map(|record| record.event).collect()
+	}
+
+	#[test]
+	fn elo_ratings() {
+		assert_eq!(BattleModule::expected_score(1200, 1200), 500);
+		assert_eq!(BattleModule::expected_score(1400, 1200), 760);
+		assert_eq!(BattleModule::expected_score(1200, 1400), 240);
+		assert_eq!(BattleModule::expected_score(3000, 1000), 990);
+
+		assert_eq!(BattleModule::rate(1200, 1200), (1216, 1184));
+		// Beating a weaker kitty earns less than beating a stronger one
+		assert_eq!(BattleModule::rate(1400, 1200), (1408, 1192));
+		assert_eq!(BattleModule::rate(1200, 1400), (1224, 1376));
+		assert_eq!(BattleModule::rate(1200, 10), (1200, 10));
+	}
+
+	#[test]
+	fn traits_are_decoded_from_dna() {
+		let traits = Traits::from_dna(&[10, 20, 30, 40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+		assert_eq!(traits, Traits { attack: 10, defence: 20, speed: 30 });
+		assert_eq!(traits.power(), 60);
+	}
+
+	#[test]
+	fn challenge_is_fought_when_accepted() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(BattleModule::challenge(Origin::signed(2), 0, 1), "Only owner can challenge");
+			assert_noop!(BattleModule::challenge(Origin::signed(1), 0, 9), "Opponent does not exist");
+			MockKitties::create(9, 1, 0);
+			assert_noop!(BattleModule::challenge(Origin::signed(1), 0, 9), "Cannot challenge own kitty");
+
+			assert_ok!(BattleModule::challenge(Origin::signed(1), 0, 1));
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::Challenged(1, 0, 1)));
+			assert_eq!(BattleModule::open_challenge(0), Some((1, 1)));
+
+			assert_noop!(BattleModule::accept_challenge(Origin::signed(2), 0, 2), "No challenge");
+			assert_noop!(BattleModule::accept_challenge(Origin::signed(3), 0, 1), "Only owner can accept challenge");
+			assert_ok!(BattleModule::accept_challenge(Origin::signed(2), 0, 1));
+
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::Fought(0, 1, 1216, 1184)));
+			assert_eq!(BattleModule::rating(0), 1216);
+			assert_eq!(BattleModule::rating(1), 1184);
+			assert_eq!(BattleModule::rating(2), INITIAL_RATING);
+			assert_eq!(BattleModule::open_challenge(0), None);
+		});
+	}
+
+	#[test]
+	fn challenge_can_be_cancelled() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(BattleModule::challenge(Origin::signed(1), 0, 1));
+
+			assert_noop!(BattleModule::cancel_challenge(Origin::signed(2), 0), "Only challenger can cancel challenge");
+			assert_ok!(BattleModule::cancel_challenge(Origin::signed(1), 0));
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::ChallengeCancelled(0)));
+			assert_noop!(BattleModule::accept_challenge(Origin::signed(2), 0, 1), "No challenge");
+
+			// A challenge does not follow the kitty to a new owner
+			assert_ok!(BattleModule::challenge(Origin::signed(1), 0, 1));
+			assert_ok!(MockKitties::transfer(&1, &5, 0));
+			assert_noop!(BattleModule::accept_challenge(Origin::signed(2), 0, 1), "Challenger kitty has changed owner");
+		});
+	}
+
+	#[test]
+	fn tournament_champion_takes_the_fees() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(BattleModule::create_tournament(Origin::signed(5), 10, 1, 5), "Invalid number of entrants");
+			assert_noop!(BattleModule::create_tournament(Origin::signed(5), 10, 5, 5), "Invalid number of entrants");
+			assert_noop!(BattleModule::create_tournament(Origin::signed(5), 10, 4, 1), "Start must be in the future");
+			assert_ok!(BattleModule::create_tournament(Origin::signed(5), 10, 4, 5));
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::TournamentCreated(0, 5, 10, 5)));
+			assert_eq!(BattleModule::tournaments_at(5), vec![0]);
+			assert_eq!(Balances::reserved_balance(&5), 5);
+
+			assert_noop!(BattleModule::enter_tournament(Origin::signed(2), 0, 0), "Only owner can enter kitty");
+			for (owner, kitty_id) in vec![(1, 0), (2, 1), (3, 2), (4, 3)] {
+				assert_ok!(BattleModule::enter_tournament(Origin::signed(owner), 0, kitty_id));
+			}
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::Entered(0, 4, 3)));
+			assert_eq!(Balances::reserved_balance(&1), 10);
+			assert_noop!(BattleModule::enter_tournament(Origin::signed(1), 0, 0), "Tournament is full");
+
+			BattleModule::on_initialize(5);
+
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::TournamentWon(0, 1, 0, 40)));
+			let rounds: Vec<_> = events().into_iter().filter_map(|event| match event {
+				TestEvent::battle(RawEvent::RoundPlayed(0, round, kitties)) => Some((round, kitties)),
+				_ => None,
+			}).collect();
+			assert_eq!(rounds.len(), 2);
+			assert_eq!(rounds[0].1.len(), 2);
+			assert_eq!(rounds[0].1[0], 0);
+			assert_eq!(rounds[1], (2, vec![0]));
+
+			// Won two fights against kitties rated as high as itself
+			assert_eq!(BattleModule::rating(0), 1232);
+			assert_eq!(BattleModule::rating(1), 1184);
+			assert_eq!(Balances::free_balance(&1), 130);
+			assert_eq!(Balances::free_balance(&2), 90);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert_eq!(Balances::reserved_balance(&5), 0);
+			assert_eq!(BattleModule::tournament(0), None);
+			assert_eq!(BattleModule::tournaments_at(5), Vec::<TournamentId>::new());
+		});
+	}
+
+	#[test]
+	fn kitty_changing_owner_forfeits_its_fee() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(BattleModule::create_tournament(Origin::signed(5), 10, 3, 5));
+			for (owner, kitty_id) in vec![(1, 0), (2, 1), (3, 2)] {
+				assert_ok!(BattleModule::enter_tournament(Origin::signed(owner), 0, kitty_id));
+			}
+			assert_noop!(BattleModule::enter_tournament(Origin::signed(1), 0, 0), "Tournament is full");
+			assert_ok!(MockKitties::transfer(&2, &5, 1));
+
+			BattleModule::on_initialize(5);
+
+			assert!(events().contains(&TestEvent::battle(RawEvent::RoundPlayed(0, 1, vec![0]))));
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::TournamentWon(0, 1, 0, 30)));
+			assert_eq!(BattleModule::rating(1), INITIAL_RATING);
+			assert_eq!(Balances::free_balance(&1), 120);
+			assert_eq!(Balances::free_balance(&2), 90);
+			assert_eq!(Balances::free_balance(&3), 90);
+		});
+	}
+
+	#[test]
+	fn tournament_without_enough_kitties_is_cancelled() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(BattleModule::create_tournament(Origin::signed(5), 10, 4, 5));
+			assert_ok!(BattleModule::enter_tournament(Origin::signed(1), 0, 0));
+			assert_eq!(Balances::free_balance(&1), 90);
+
+			BattleModule::on_initialize(5);
+
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::TournamentCancelled(0)));
+			assert_eq!(Balances::free_balance(&1), 100);
+			assert_eq!(Balances::free_balance(&5), 100);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_noop!(BattleModule::enter_tournament(Origin::signed(2), 0, 1), "Tournament does not exist");
+		});
+	}
+
+	#[test]
+	fn kitties_that_cannot_fight_sit_out() {
+		with_externalities(&mut new_test_ext(), || {
+			MockKitties::bench(2);
+			assert_noop!(BattleModule::challenge(Origin::signed(3), 2, 1), "Kitty cannot fight");
+			assert_noop!(BattleModule::challenge(Origin::signed(1), 0, 2), "Opponent cannot fight");
+
+			assert_ok!(BattleModule::challenge(Origin::signed(1), 0, 1));
+			MockKitties::bench(0);
+			assert_noop!(BattleModule::accept_challenge(Origin::signed(2), 0, 1), "Opponent cannot fight");
+
+			// A kitty benched after entering forfeits
+			assert_ok!(BattleModule::create_tournament(Origin::signed(5), 10, 4, 5));
+			assert_noop!(BattleModule::enter_tournament(Origin::signed(3), 0, 2), "Kitty cannot fight");
+			for (owner, kitty_id) in vec![(2, 1), (4, 3)] {
+				assert_ok!(BattleModule::enter_tournament(Origin::signed(owner), 0, kitty_id));
+			}
+			MockKitties::bench(3);
+
+			BattleModule::on_initialize(5);
+
+			assert_eq!(last_event(), TestEvent::battle(RawEvent::TournamentWon(0, 2, 1, 20)));
+			assert_eq!(Balances::free_balance(&4), 90);
+		});
+	}
+
+	#[test]
+	fn tournaments_per_block_are_capped() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(BattleModule::create_tournament(Origin::signed(5), 0, 4, 5));
+			assert_ok!(BattleModule::create_tournament(Origin::signed(5), 0, 4, 5));
+			assert_noop!(BattleModule::create_tournament(Origin::signed(1), 0, 4, 5), "Too many tournaments at this block");
+			assert_eq!(Balances::reserved_balance(&5), 10);
+
+			// Organisers without the deposit cannot create tournaments
+			assert!(BattleModule::create_tournament(Origin::signed(6), 0, 4, 6).is_err());
+
+			assert_ok!(BattleModule::create_tournament(Origin::signed(1), 0, 4, 6));
+			assert_eq!(BattleModule::tournaments_at(6), vec![2]);
+
+			BattleModule::on_initialize(5);
+			assert_eq!(Balances::reserved_balance(&5), 0);
+		});
+	}
+}
@@ -0,0 +1,685 @@
+use support::{
+	decl_module, decl_storage, decl_event, ensure, StorageValue, StorageMap,
+	traits::{Currency, ReservableCurrency, Get},
+};
+use sr_primitives::traits::{StaticLookup, Zero, Saturating, SaturatedConversion};
+use codec::{Encode, Decode};
+use system::ensure_signed;
+use rstd::result;
+use crate::nft::NonFungibleAsset;
+
+pub trait Trait: system::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// Pays for buyouts
+	type Currency: ReservableCurrency<Self::AccountId>;
+	/// The assets that can be vaulted
+	type Nft: NonFungibleAsset<Self::AccountId>;
+	/// Number of shares minted for each vault
+	type SharesPerVault: Get<Shares>;
+	/// Number of blocks a buyout stays open for higher bids
+	type BuyoutPeriod: Get<Self::BlockNumber>;
+}
+
+pub type VaultId = u64;
+pub type Shares = u64;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type AssetIdOf<T> = <<T as Trait>::Nft as NonFungibleAsset<<T as system::Trait>::AccountId>>::AssetId;
+type VaultOf<T> = Vault<<T as system::Trait>::AccountId, AssetIdOf<T>>;
+type BuyoutOf<T> = Buyout<<T as system::Trait>::AccountId, BalanceOf<T>, <T as system::Trait>::BlockNumber>;
+
+/// An asset locked in exchange for fungible shares
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode, Clone)]
+pub struct Vault<AccountId, AssetId> {
+	/// Locked until it is redeemed or bought out
+	pub asset: AssetId,
+	/// Still owns the asset while it is vaulted
+	pub depositor: AccountId,
+	/// Number of shares minted
+	pub supply: Shares,
+	/// Number of shares not burned yet
+	pub outstanding: Shares,
+}
+
+/// An offer to buy a vaulted asset from all the other share holders
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode, Clone)]
+pub struct Buyout<AccountId, Balance, BlockNumber> {
+	pub bidder: AccountId,
+	/// Price of the whole asset. The bidder pays it for the shares of the other holders.
+	pub price: Balance,
+	/// Reserved from the bidder to pay the other holders
+	pub reserved: Balance,
+	/// Last block to outbid, or the block the buyout was settled
+	pub end: BlockNumber,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Fractional {
+		/// Get vault. Vaults stay here until all their shares are burned.
+		pub Vaults get(vault): map VaultId => Option<VaultOf<T>>;
+		/// The id of the next vault
+		pub NextVaultId get(next_vault_id): VaultId;
+		/// Get the shares of an account in a vault
+		pub ShareBalances get(share_balance): map (VaultId, T::AccountId) => Shares;
+		/// Get the open buyout of a vault
+		pub Buyouts get(buyout): map VaultId => Option<BuyoutOf<T>>;
+		/// Get the settled buyout of a vault. The remaining holders are paid out of it.
+		pub Payouts get(payout): map VaultId => Option<BuyoutOf<T>>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::BlockNumber,
+		AssetId = AssetIdOf<T>,
+		Balance = BalanceOf<T>,
+	{
+		/// An asset is vaulted. (vault_id, asset_id, depositor, supply)
+		Fractionalised(VaultId, AssetId, AccountId, Shares),
+		/// Shares are transferred. (vault_id, from, to, amount)
+		SharesTransferred(VaultId, AccountId, AccountId, Shares),
+		/// The asset is redeemed by the holder of all shares. (vault_id, holder)
+		Redeemed(VaultId, AccountId),
+		/// A buyout is opened. (vault_id, bidder, price, end)
+		BuyoutStarted(VaultId, AccountId, Balance, BlockNumber),
+		/// A buyout is outbid. (vault_id, bidder, price)
+		BuyoutBid(VaultId, AccountId, Balance),
+		/// The asset is bought out. (vault_id, buyer, price)
+		BoughtOut(VaultId, AccountId, Balance),
+		/// A buyout ends without a sale as the bidder cannot take the asset, the bid is refunded. (vault_id, bidder)
+		BuyoutCancelled(VaultId, AccountId),
+		/// A holder sells their shares to the buyer. (vault_id, holder, amount paid)
+		ProceedsClaimed(VaultId, AccountId, Balance),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Lock an asset in a new vault and get all of its shares
+		pub fn fractionalise(origin, asset_id: AssetIdOf<T>) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(T::Nft::owner_of(asset_id) == Some(sender.clone()), "Only owner can fractionalise asset");
+
+			let vault_id = Self::next_vault_id();
+			let next_vault_id = vault_id.checked_add(1).ok_or("Vaults count overflow")?;
+			let supply = T::SharesPerVault::get();
+			ensure!(!supply.is_zero(), "Vault has no shares");
+
+			T::Nft::lock(asset_id)?;
+
+			<Vaults<T>>::insert(vault_id, Vault {
+				asset: asset_id,
+				depositor: sender.clone(),
+				supply,
+				outstanding: supply,
+			});
+			<ShareBalances<T>>::insert((vault_id, sender.clone()), supply);
+			NextVaultId::put(next_vault_id);
+
+			Self::deposit_event(RawEvent::Fractionalised(vault_id, asset_id, sender, supply));
+		}
+
+		/// Transfer shares of a vault. Shares cannot move while a buyout is open.
+		pub fn transfer_shares(origin, vault_id: VaultId, to: <T::Lookup as StaticLookup>::Source, amount: Shares) {
+			let sender = ensure_signed(origin)?;
+			let to = T::Lookup::lookup(to)?;
+
+			ensure!(<Vaults<T>>::exists(vault_id), "Vault does not exist");
+			ensure!(!<Buyouts<T>>::exists(vault_id), "Buyout in progress");
+
+			let from_balance = Self::share_balance((vault_id, sender.clone()));
+			ensure!(from_balance >= amount, "Not enough shares");
+
+			Self::set_share_balance(vault_id, &sender, from_balance - amount);
+			let to_balance = Self::share_balance((vault_id, to.clone()));
+			Self::set_share_balance(vault_id, &to, to_balance.saturating_add(amount));
+
+			Self::deposit_event(RawEvent::SharesTransferred(vault_id, sender, to, amount));
+		}
+
+		/// Burn all shares of a vault to take its asset
+		pub fn redeem(origin, vault_id: VaultId) {
+			let sender = ensure_signed(origin)?;
+
+			let vault = Self::vault(vault_id).ok_or("Vault does not exist")?;
+			ensure!(!<Buyouts<T>>::exists(vault_id), "Buyout in progress");
+			ensure!(!<Payouts<T>>::exists(vault_id), "Vault is bought out");
+			ensure!(Self::share_balance((vault_id, sender.clone())) == vault.supply, "Must hold all shares");
+
+			Self::release(&vault, &sender)?;
+			<ShareBalances<T>>::remove((vault_id, sender.clone()));
+			<Vaults<T>>::remove(vault_id);
+
+			Self::deposit_event(RawEvent::Redeemed(vault_id, sender));
+		}
+
+		/// Offer `price` for the whole asset, reserving the share of it owed to the other holders.
+		/// Other holders can outbid until the buyout ends.
+		pub fn start_buyout(origin, vault_id: VaultId, price: BalanceOf<T>) {
+			let sender = ensure_signed(origin)?;
+
+			let vault = Self::vault(vault_id).ok_or("Vault does not exist")?;
+			ensure!(!<Buyouts<T>>::exists(vault_id), "Buyout in progress");
+			ensure!(!<Payouts<T>>::exists(vault_id), "Vault is bought out");
+			ensure!(Self::can_take(&vault, &sender), "Bidder cannot take the asset");
+
+			let reserved = Self::buyout_cost(vault_id, &vault, &sender, price)?;
+			T::Currency::reserve(&sender, reserved)?;
+
+			let end = <system::Module<T>>::block_number().saturating_add(T::BuyoutPeriod::get());
+			<Buyouts<T>>::insert(vault_id, Buyout {
+				bidder: sender.clone(),
+				price,
+				reserved,
+				end,
+			});
+
+			Self::deposit_event(RawEvent::BuyoutStarted(vault_id, sender, price, end));
+		}
+
+		/// Outbid the open buyout of a vault, refunding the previous bidder
+		pub fn bid(origin, vault_id: VaultId, price: BalanceOf<T>) {
+			let sender = ensure_signed(origin)?;
+
+			let vault = Self::vault(vault_id).ok_or("Vault does not exist")?;
+			let buyout = Self::buyout(vault_id).ok_or("No buyout in progress")?;
+			ensure!(<system::Module<T>>::block_number() <= buyout.end, "Buyout is over");
+			ensure!(price > buyout.price, "Bid is too low");
+			ensure!(Self::can_take(&vault, &sender), "Bidder cannot take the asset");
+
+			let reserved = Self::buyout_cost(vault_id, &vault, &sender, price)?;
+
+			T::Currency::unreserve(&buyout.bidder, buyout.reserved);
+			if let Err(e) = T::Currency::reserve(&sender, reserved) {
+				// Just unreserved, so this cannot fail
+				let _ = T::Currency::reserve(&buyout.bidder, buyout.reserved);
+				return Err(e);
+			}
+
+			<Buyouts<T>>::insert(vault_id, Buyout {
+				bidder: sender.clone(),
+				price,
+				reserved,
+				end: buyout.end,
+			});
+
+			Self::deposit_event(RawEvent::BuyoutBid(vault_id, sender, price));
+		}
+
+		/// Give the asset to the winner of a buyout that has ended.
+		/// The other holders can then claim their share of the price.
+		/// If the winner can no longer take the asset, the bid is refunded and the vault is open for a new buyout.
+		pub fn settle_buyout(origin, vault_id: VaultId) {
+			ensure_signed(origin)?;
+
+			let mut vault = Self::vault(vault_id).ok_or("Vault does not exist")?;
+			let mut buyout = Self::buyout(vault_id).ok_or("No buyout in progress")?;
+			let now = <system::Module<T>>::block_number();
+			ensure!(now > buyout.end, "Buyout is not over");
+
+			// The winner may have taken other assets since the bid
+			if !Self::can_take(&vault, &buyout.bidder) {
+				T::Currency::unreserve(&buyout.bidder, buyout.reserved);
+				<Buyouts<T>>::remove(vault_id);
+				Self::deposit_event(RawEvent::BuyoutCancelled(vault_id, buyout.bidder));
+				return Ok(());
+			}
+
+			Self::release(&vault, &buyout.bidder)?;
+
+			let bidder_shares = <ShareBalances<T>>::take((vault_id, buyout.bidder.clone()));
+			vault.outstanding = vault.outstanding.saturating_sub(bidder_shares);
+			<Buyouts<T>>::remove(vault_id);
+			Self::deposit_event(RawEvent::BoughtOut(vault_id, buyout.bidder.clone(), buyout.price));
+
+			if vault.outstanding.is_zero() {
+				T::Currency::unreserve(&buyout.bidder, buyout.reserved);
+				<Vaults<T>>::remove(vault_id);
+			} else {
+				buyout.end = now;
+				<Vaults<T>>::insert(vault_id, vault);
+				<Payouts<T>>::insert(vault_id, buyout);
+			}
+		}
+
+		/// Burn shares of a bought out vault for their part of the price
+		pub fn claim_proceeds(origin, vault_id: VaultId) {
+			let sender = ensure_signed(origin)?;
+
+			let mut vault = Self::vault(vault_id).ok_or("Vault does not exist")?;
+			let mut payout = Self::payout(vault_id).ok_or("Vault is not bought out")?;
+			let shares = Self::share_balance((vault_id, sender.clone()));
+			ensure!(!shares.is_zero(), "No shares to claim");
+
+			let amount = Self::share_value(&vault, payout.price, shares).min(payout.reserved);
+			// The buyer's reserve can only fall short if it was slashed elsewhere
+			let unpaid = T::Currency::repatriate_reserved(&payout.bidder, &sender, amount)?;
+			let paid = amount.saturating_sub(unpaid);
+
+			<ShareBalances<T>>::remove((vault_id, sender.clone()));
+			vault.outstanding = vault.outstanding.saturating_sub(shares);
+			payout.reserved = payout.reserved.saturating_sub(paid);
+
+			if vault.outstanding.is_zero() {
+				// Refund what is left over from rounding
+				T::Currency::unreserve(&payout.bidder, payout.reserved);
+				<Vaults<T>>::remove(vault_id);
+				<Payouts<T>>::remove(vault_id);
+			} else {
+				<Vaults<T>>::insert(vault_id, vault);
+				<Payouts<T>>::insert(vault_id, payout);
+			}
+
+			Self::deposit_event(RawEvent::ProceedsClaimed(vault_id, sender, paid));
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	fn set_share_balance(vault_id: VaultId, who: &T::AccountId, balance: Shares) {
+		if balance.is_zero() {
+			<ShareBalances<T>>::remove((vault_id, who.clone()));
+		} else {
+			<ShareBalances<T>>::insert((vault_id, who.clone()), balance);
+		}
+	}
+
+	/// The part of `price` that `shares` of the vault are worth, rounded down
+	fn share_value(vault: &VaultOf<T>, price: BalanceOf<T>, shares: Shares) -> BalanceOf<T> {
+		let shares = shares.saturated_into::<BalanceOf<T>>();
+		let supply = vault.supply.saturated_into::<BalanceOf<T>>();
+		price.saturating_mul(shares) / supply
+	}
+
+	/// What `bidder` has to reserve to buy the shares of all the other holders at `price`
+	fn buyout_cost(vault_id: VaultId, vault: &VaultOf<T>, bidder: &T::AccountId, price: BalanceOf<T>) -> result::Result<BalanceOf<T>, &'static str> {
+		let own = Self::share_balance((vault_id, bidder.clone()));
+		ensure!(!own.is_zero(), "Only share holders can buy out");
+		ensure!(own < vault.supply, "Holder of all shares can redeem");
+
+		Ok(price.saturating_sub(Self::share_value(vault, price, own)))
+	}
+
+	/// Whether `who` can take the asset of the vault. The depositor still owns it.
+	fn can_take(vault: &VaultOf<T>, who: &T::AccountId) -> bool {
+		vault.depositor == *who || T::Nft::can_receive(who)
+	}
+
+	/// Unlock the asset of the vault and move it to `to`, locking it again if the transfer fails
+	fn release(vault: &VaultOf<T>, to: &T::AccountId) -> result::Result<(), &'static str> {
+		T::Nft::unlock(vault.asset)?;
+
+		if vault.depositor != *to {
+			if let Err(e) = T::Nft::transfer(&vault.depositor, to, vault.asset) {
+				T::Nft::lock(vault.asset)?;
+				return Err(e);
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Tests for Fractional module
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use primitives::{H256, Blake2Hasher};
+	use support::{impl_outer_origin, impl_outer_event, assert_ok, assert_noop, parameter_types};
+	use support::traits::{LockIdentifier, OnFreeBalanceZero};
+	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+	use sr_primitives::weights::Weight;
+	use sr_primitives::Perbill;
+	use crate::kitties::{self, ReapPolicy};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	mod fractional {
+		pub use crate::fractional::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for Test {
+			balances<T>,
+			kitties<T>,
+			fractional<T>,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Call = ();
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type WeightMultiplierUpdate = ();
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+		pub const TransactionBaseFee: u64 = 0;
+		pub const TransactionByteFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = Kitties;
+		type OnNewAccount = ();
+		type Event = TestEvent;
+		type TransactionPayment = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+		type TransactionBaseFee = TransactionBaseFee;
+		type TransactionByteFee = TransactionByteFee;
+		type WeightToFee = ();
+	}
+	parameter_types! {
+		pub const KittyReapPolicy: ReapPolicy = ReapPolicy::Shelter;
+		pub const KittiesLockId: LockIdentifier = *b"kitties ";
+		pub const NameByteDeposit: u64 = 10;
+		pub const MaxNameLength: u32 = 8;
+		pub const MaxKittiesPerAccount: u32 = 10;
+		pub const MaxGen0Kitties: u32 = 100;
+		pub const BreedingFee: u64 = 0;
+		pub const HatchingPeriod: u64 = 1;
+		pub const KittenPeriod: u64 = 0;
+		pub const AdultPeriod: u64 = u64::max_value();
+		pub const Lifespan: Option<u64> = None;
+	}
+	impl kitties::Trait for Test {
+		type Event = TestEvent;
+		type KittyIndex = u32;
+		type Currency = Balances;
+		type KittyReapPolicy = KittyReapPolicy;
+		type ReapLockId = KittiesLockId;
+		type NameByteDeposit = NameByteDeposit;
+		type MaxNameLength = MaxNameLength;
+		type MaxKittiesPerAccount = MaxKittiesPerAccount;
+		type MaxGen0Kitties = MaxGen0Kitties;
+		type BreedingBaseFee = BreedingFee;
+		type BreedingFeePerKitty = BreedingFee;
+		type BreedingFeePerGeneration = BreedingFee;
+		type BreedingFeeDestination = ();
+		type HatchingPeriod = HatchingPeriod;
+		type KittenPeriod = KittenPeriod;
+		type AdultPeriod = AdultPeriod;
+		type Lifespan = Lifespan;
+		type OnKittyCreated = ();
+		type OnKittyTransferred = ();
+	}
+	parameter_types! {
+		pub const SharesPerVault: Shares = 100;
+		pub const BuyoutPeriod: u64 = 5;
+	}
+	impl Trait for Test {
+		type Event = TestEvent;
+		type Currency = Balances;
+		type Nft = Kitties;
+		type SharesPerVault = SharesPerVault;
+		type BuyoutPeriod = BuyoutPeriod;
+	}
+	type FractionalModule = Module<Test>;
+	type Kitties = kitties::Module<Test>;
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+
+	// Account 1 owns kitty 0, accounts 1, 2 and 3 have 100 each
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![(1, 100), (2, 100), (3, 100)],
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+
+		let mut ext: runtime_io::TestExternalities<Blake2Hasher> = t.into();
+		with_externalities(&mut ext, || {
+			System::set_block_number(1);
+			assert_ok!(Kitties::create(Origin::signed(1)));
+		});
+		ext
+	}
+
+	fn last_event() -> TestEvent {
+		System::events().pop().expect("Event expected").event
+	}
+
+	// Vault kitty 0 and share it 60 / 30 / 10 between accounts 1, 2 and 3
+	fn share_kitty() {
+		assert_ok!(FractionalModule::fractionalise(Origin::signed(1), 0));
+		assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), 0, 2, 30));
+		assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), 0, 3, 10));
+	}
+
+	#[test]
+	fn fractionalise_locks_asset_and_mints_shares() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(FractionalModule::fractionalise(Origin::signed(2), 0), "Only owner can fractionalise asset");
+			assert_ok!(FractionalModule::fractionalise(Origin::signed(1), 0));
+
+			assert_eq!(last_event(), TestEvent::fractional(RawEvent::Fractionalised(0, 0, 1, 100)));
+			assert_eq!(FractionalModule::vault(0), Some(Vault {
+				asset: 0,
+				depositor: 1,
+				supply: 100,
+				outstanding: 100,
+			}));
+			assert_eq!(FractionalModule::share_balance((0, 1)), 100);
+			assert_eq!(FractionalModule::next_vault_id(), 1);
+			assert!(Kitties::is_locked(0));
+
+			// The vaulted kitty cannot be moved
+			assert_noop!(Kitties::transfer(Origin::signed(1), 2, 0), "Kitty is locked");
+			assert_noop!(Kitties::ask(Origin::signed(1), 0, Some(10), None), "Kitty is locked");
+			assert_noop!(FractionalModule::fractionalise(Origin::signed(1), 0), "Kitty is locked");
+		});
+	}
+
+	#[test]
+	fn shares_can_be_transferred() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(FractionalModule::fractionalise(Origin::signed(1), 0));
+
+			assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), 0, 2, 40));
+			assert_eq!(last_event(), TestEvent::fractional(RawEvent::SharesTransferred(0, 1, 2, 40)));
+			assert_eq!(FractionalModule::share_balance((0, 1)), 60);
+			assert_eq!(FractionalModule::share_balance((0, 2)), 40);
+
+			assert_noop!(FractionalModule::transfer_shares(Origin::signed(2), 0, 3, 41), "Not enough shares");
+			assert_noop!(FractionalModule::transfer_shares(Origin::signed(2), 1, 3, 1), "Vault does not exist");
+
+			assert_ok!(FractionalModule::transfer_shares(Origin::signed(2), 0, 2, 40));
+			assert_eq!(FractionalModule::share_balance((0, 2)), 40);
+
+			assert_ok!(FractionalModule::transfer_shares(Origin::signed(2), 0, 3, 40));
+			assert!(!<ShareBalances<Test>>::exists((0, 2)));
+			assert_eq!(FractionalModule::share_balance((0, 3)), 40);
+		});
+	}
+
+	#[test]
+	fn holder_of_all_shares_redeems_asset() {
+		with_externalities(&mut new_test_ext(), || {
+			share_kitty();
+
+			assert_noop!(FractionalModule::redeem(Origin::signed(1), 0), "Must hold all shares");
+
+			assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), 0, 2, 60));
+			assert_ok!(FractionalModule::transfer_shares(Origin::signed(3), 0, 2, 10));
+			assert_ok!(FractionalModule::redeem(Origin::signed(2), 0));
+
+			assert_eq!(last_event(), TestEvent::fractional(RawEvent::Redeemed(0, 2)));
+			assert_eq!(FractionalModule::vault(0), None);
+			assert!(!<ShareBalances<Test>>::exists((0, 2)));
+			assert!(!Kitties::is_locked(0));
+			assert_eq!(Kitties::kitty_owner(0), Some(2));
+			assert_eq!(Kitties::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn vaulted_asset_of_reaped_depositor_stays_redeemable() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(FractionalModule::fractionalise(Origin::signed(1), 0));
+			assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), 0, 2, 100));
+
+			<Kitties as OnFreeBalanceZero<u64>>::on_free_balance_zero(&1);
+			assert_eq!(Kitties::kitty_owner(0), Some(1));
+
+			assert_ok!(FractionalModule::redeem(Origin::signed(2), 0));
+			assert_eq!(Kitties::kitty_owner(0), Some(2));
+			assert_eq!(Kitties::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn buyout_pays_remaining_holders() {
+		with_externalities(&mut new_test_ext(), || {
+			share_kitty();
+
+			assert_noop!(FractionalModule::start_buyout(Origin::signed(4), 0, 50), "Only share holders can buy out");
+			// Account 2 pays 50 for the 70 shares it does not hold
+			assert_ok!(FractionalModule::start_buyout(Origin::signed(2), 0, 50));
+			assert_eq!(last_event(), TestEvent::fractional(RawEvent::BuyoutStarted(0, 2, 50, 6)));
+			assert_eq!(Balances::reserved_balance(&2), 35);
+
+			assert_noop!(FractionalModule::transfer_shares(Origin::signed(1), 0, 3, 10), "Buyout in progress");
+			assert_noop!(FractionalModule::start_buyout(Origin::signed(3), 0, 60), "Buyout in progress");
+			assert_noop!(FractionalModule::bid(Origin::signed(3), 0, 50), "Bid is too low");
+			// Account 3 cannot afford the bid, account 2 keeps its reserve
+			assert!(FractionalModule::bid(Origin::signed(3), 0, 200).is_err());
+			assert_eq!(FractionalModule::buyout(0).map(|b| b.bidder), Some(2));
+			assert_eq!(Balances::reserved_balance(&2), 35);
+
+			// Account 3 outbids and account 2 is refunded
+			assert_ok!(FractionalModule::bid(Origin::signed(3), 0, 60));
+			assert_eq!(last_event(), TestEvent::fractional(RawEvent::BuyoutBid(0, 3, 60)));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert_eq!(Balances::reserved_balance(&3), 54);
+
+			assert_noop!(FractionalModule::settle_buyout(Origin::signed(1), 0), "Buyout is not over");
+			System::set_block_number(7);
+			assert_noop!(FractionalModule::bid(Origin::signed(2), 0, 70), "Buyout is over");
+			assert_ok!(FractionalModule::settle_buyout(Origin::signed(1), 0));
+
+			assert_eq!(last_event(), TestEvent::fractional(RawEvent::BoughtOut(0, 3, 60)));
+			assert_eq!(Kitties::kitty_owner(0), Some(3));
+			assert!(!Kitties::is_locked(0));
+			assert_noop!(FractionalModule::redeem(Origin::signed(1), 0), "Vault is bought out");
+			assert_noop!(FractionalModule::claim_proceeds(Origin::signed(3), 0), "No shares to claim");
+
+			assert_ok!(FractionalModule::claim_proceeds(Origin::signed(1), 0));
+			assert_eq!(last_event(), TestEvent::fractional(RawEvent::ProceedsClaimed(0, 1, 36)));
+			assert_ok!(FractionalModule::claim_proceeds(Origin::signed(2), 0));
+
+			assert_eq!(Balances::free_balance(&1), 136);
+			assert_eq!(Balances::free_balance(&2), 118);
+			assert_eq!(Balances::free_balance(&3), 46);
+			assert_eq!(Balances::reserved_balance(&3), 0);
+			assert_eq!(FractionalModule::vault(0), None);
+			assert_eq!(FractionalModule::payout(0), None);
+			assert_eq!(Kitties::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn last_claim_refunds_rounding() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(FractionalModule::fractionalise(Origin::signed(1), 0));
+			assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), 0, 2, 33));
+			assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), 0, 3, 34));
+
+			assert_noop!(FractionalModule::claim_proceeds(Origin::signed(1), 0), "Vault is not bought out");
+
+			// 10 - 10 * 34 / 100 rounded down
+			assert_ok!(FractionalModule::start_buyout(Origin::signed(3), 0, 10));
+			assert_eq!(Balances::reserved_balance(&3), 7);
+			System::set_block_number(7);
+			assert_ok!(FractionalModule::settle_buyout(Origin::signed(2), 0));
+
+			// 33 shares are worth 3 each
+			assert_ok!(FractionalModule::claim_proceeds(Origin::signed(1), 0));
+			assert_ok!(FractionalModule::claim_proceeds(Origin::signed(2), 0));
+
+			assert_eq!(Balances::free_balance(&1), 103);
+			assert_eq!(Balances::free_balance(&2), 103);
+			assert_eq!(Balances::free_balance(&3), 94);
+			assert_eq!(Balances::reserved_balance(&3), 0);
+		});
+	}
+
+	#[test]
+	fn failed_settlement_refunds_the_bidder() {
+		with_externalities(&mut new_test_ext(), || {
+			share_kitty();
+			assert_noop!(FractionalModule::start_buyout(Origin::signed(1), 1, 50), "Vault does not exist");
+			assert_ok!(FractionalModule::start_buyout(Origin::signed(2), 0, 50));
+			// The buyer cannot take one more kitty
+			for _ in 0..MaxKittiesPerAccount::get() {
+				assert_ok!(Kitties::create(Origin::signed(2)));
+			}
+
+			System::set_block_number(7);
+			assert_ok!(FractionalModule::settle_buyout(Origin::signed(1), 0));
+			assert_eq!(last_event(), TestEvent::fractional(RawEvent::BuyoutCancelled(0, 2)));
+			assert_eq!(FractionalModule::buyout(0), None);
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert_eq!(FractionalModule::share_balance((0, 2)), 30);
+			assert!(Kitties::is_locked(0));
+			assert_eq!(Kitties::kitty_owner(0), Some(1));
+
+			// The vault is open for another buyout
+			assert_noop!(FractionalModule::start_buyout(Origin::signed(2), 0, 50), "Bidder cannot take the asset");
+			assert_ok!(FractionalModule::start_buyout(Origin::signed(3), 0, 50));
+		});
+	}
+
+	#[test]
+	fn bidder_must_be_able_to_take_the_asset() {
+		with_externalities(&mut new_test_ext(), || {
+			share_kitty();
+			for _ in 0..MaxKittiesPerAccount::get() {
+				assert_ok!(Kitties::create(Origin::signed(3)));
+			}
+
+			assert_noop!(FractionalModule::start_buyout(Origin::signed(3), 0, 50), "Bidder cannot take the asset");
+			assert_ok!(FractionalModule::start_buyout(Origin::signed(2), 0, 50));
+			assert_noop!(FractionalModule::bid(Origin::signed(3), 0, 60), "Bidder cannot take the asset");
+			// The depositor still owns the asset
+			for _ in 0..MaxKittiesPerAccount::get() - 1 {
+				assert_ok!(Kitties::create(Origin::signed(1)));
+			}
+			assert_ok!(FractionalModule::bid(Origin::signed(1), 0, 60));
+		});
+	}
+}
@@ -1,49 +1,382 @@
 use support::{
 	decl_module, decl_storage, decl_event, ensure, StorageValue, StorageMap,
-	Parameter, traits::Currency
+	Parameter, traits::{
+		Currency, LockableCurrency, ReservableCurrency, LockIdentifier, WithdrawReasons, WithdrawReason,
+		ExistenceRequirement, OnUnbalanced, Get, Imbalance,
+	}
+};
+use sr_primitives::traits::{
+	SimpleArithmetic, Bounded, Member, OnFreeBalanceZero, StaticLookup, Zero, One, Saturating, SaturatedConversion
 };
-use sr_primitives::traits::{SimpleArithmetic, Bounded, Member};
 use codec::{Encode, Decode};
 use runtime_io::blake2_128;
-use system::ensure_signed;
-use rstd::result;
-use crate::linked_item::{LinkedList, LinkedItem};
+use system::{ensure_signed, ensure_root};
+use rstd::{result, prelude::*, collections::btree_set::BTreeSet};
+use crate::linked_item::{LinkedList, LinkedItem, LinkError, SortedLinkedList, Ascending};
+use crate::nft::{NonFungibleAsset, AssetDna};
 
-pub trait Trait: system::Trait {
-	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+pub trait Trait<I: Instance = DefaultInstance>: system::Trait {
+	type Event: From<Event<Self, I>> + Into<<Self as system::Trait>::Event>;
 	type KittyIndex: Parameter + Member + SimpleArithmetic + Bounded + Default + Copy;
-	type Currency: Currency<Self::AccountId>;
+	type Currency: LockableCurrency<Self::AccountId, Moment = Self::BlockNumber> + ReservableCurrency<Self::AccountId>;
+	/// What happens to the kitties of an account that is reaped
+	type KittyReapPolicy: Get<ReapPolicy>;
+	/// The lock of `ReapPolicy::BlockReaping`. Must differ between instances.
+	type ReapLockId: Get<LockIdentifier>;
+	/// The deposit reserved for each byte of a kitty name
+	type NameByteDeposit: Get<BalanceOf<Self, I>>;
+	/// The longest name a kitty can have, in bytes
+	type MaxNameLength: Get<u32>;
+	/// The most kitties an account can own, until root sets another limit
+	type MaxKittiesPerAccount: Get<u32>;
+	/// The most kitties that can be created with `create`. Bred kitties do not count.
+	type MaxGen0Kitties: Get<u32>;
+	/// The part of the breeding fee paid for any breeding. The breeding fees apply until root sets others.
+	type BreedingBaseFee: Get<BalanceOf<Self, I>>;
+	/// The part of the breeding fee paid for each existing kitty
+	type BreedingFeePerKitty: Get<BalanceOf<Self, I>>;
+	/// The part of the breeding fee paid for each generation of the older parent
+	type BreedingFeePerGeneration: Get<BalanceOf<Self, I>>;
+	/// Where the breeding fees go
+	type BreedingFeeDestination: OnUnbalanced<NegativeImbalanceOf<Self, I>>;
+	/// Number of blocks an egg takes to hatch. Eggs hatch at least one block after they are laid.
+	type HatchingPeriod: Get<Self::BlockNumber>;
+	/// Number of blocks a kitty stays a kitten after it is born
+	type KittenPeriod: Get<Self::BlockNumber>;
+	/// Number of blocks a kitty stays an adult once it grows up
+	type AdultPeriod: Get<Self::BlockNumber>;
+	/// The age at which an elder becomes legendary. `None` if kitties stay elders.
+	type Lifespan: Get<Option<Self::BlockNumber>>;
+	/// Called when a kitty is created or hatches, including into the shelter
+	type OnKittyCreated: OnKittyCreated<Self::AccountId, Self::KittyIndex>;
+	/// Called when an account receives a kitty from another account or from the shelter
+	type OnKittyTransferred: OnKittyTransferred<Self::AccountId, Self::KittyIndex, BalanceOf<Self, I>>;
+}
+
+/// Lets other modules react to new kitties. Bred kitties are created when their egg hatches, not when it is laid.
+pub trait OnKittyCreated<AccountId, KittyIndex> {
+	/// `owner` is `None` if the kitty hatches into the shelter
+	fn on_kitty_created(owner: Option<&AccountId>, kitty_id: KittyIndex);
+}
+
+/// Lets other modules react to accounts receiving kitties, including co-owners added to a kitty.
+/// Kitties taken into the shelter are reported by the `Sheltered` event only.
+pub trait OnKittyTransferred<AccountId, KittyIndex, Balance> {
+	/// `from` is `None` if the kitty is adopted from the shelter.
+	/// `price` is the price paid if the kitty is sold.
+	fn on_kitty_transferred(from: Option<&AccountId>, to: &AccountId, kitty_id: KittyIndex, price: Option<Balance>);
+}
+
+impl<AccountId, KittyIndex> OnKittyCreated<AccountId, KittyIndex> for () {
+	fn on_kitty_created(_: Option<&AccountId>, _: KittyIndex) {}
+}
+
+impl<AccountId, KittyIndex, Balance> OnKittyTransferred<AccountId, KittyIndex, Balance> for () {
+	fn on_kitty_transferred(_: Option<&AccountId>, _: &AccountId, _: KittyIndex, _: Option<Balance>) {}
+}
+
+/// Implement the hooks for tuples of subscribers, calling each in order
+macro_rules! impl_kitty_hooks_for_tuples {
+	() => {};
+	( $first:ident $(, $rest:ident)* ) => {
+		impl<AccountId, KittyIndex: Copy, $first, $($rest),*> OnKittyCreated<AccountId, KittyIndex> for ($first, $($rest,)*) where
+			$first: OnKittyCreated<AccountId, KittyIndex>,
+			$($rest: OnKittyCreated<AccountId, KittyIndex>,)*
+		{
+			fn on_kitty_created(owner: Option<&AccountId>, kitty_id: KittyIndex) {
+				$first::on_kitty_created(owner, kitty_id);
+				$($rest::on_kitty_created(owner, kitty_id);)*
+			}
+		}
+
+		impl<AccountId, KittyIndex: Copy, Balance: Copy, $first, $($rest),*> OnKittyTransferred<AccountId, KittyIndex, Balance>
+			for ($first, $($rest,)*) where
+			$first: OnKittyTransferred<AccountId, KittyIndex, Balance>,
+			$($rest: OnKittyTransferred<AccountId, KittyIndex, Balance>,)*
+		{
+			fn on_kitty_transferred(from: Option<&AccountId>, to: &AccountId, kitty_id: KittyIndex, price: Option<Balance>) {
+				$first::on_kitty_transferred(from, to, kitty_id, price);
+				$($rest::on_kitty_transferred(from, to, kitty_id, price);)*
+			}
+		}
+
+		impl_kitty_hooks_for_tuples!($($rest),*);
+	};
+}
+
+impl_kitty_hooks_for_tuples!(A, B, C, D, E, F, G, H);
+
+/// The maximum number of kitties returned by one `kitties_of` call
+pub const MAX_KITTIES_PAGE: u32 = 100;
+
+/// The maximum number of listings walked to find the place of a new listing
+pub const MAX_LISTING_STEPS: u32 = 100;
+
+/// The number of blocks an egg is tried to hatch in before it is discarded
+pub const MAX_HATCH_ATTEMPTS: u32 = 10;
+
+/// The maximum number of kitties and list values walked per block to record the lengths of lists
+/// written before lengths were recorded
+pub const MAX_LENGTH_RECORD_STEPS: u32 = 100;
+
+/// How the module protects the kitties of accounts whose balance drops below the existential deposit
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReapPolicy {
+	/// Move the kitties of a reaped account to the shelter, where anyone can adopt them
+	Shelter,
+	/// Lock the existential deposit of every kitty owner so the account cannot be reaped.
+	/// Kitties of an account that is still reaped (e.g. slashed) go to the shelter.
+	BlockReaping,
+}
+
+/// The stage of life of a kitty, derived from its age
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum LifeStage {
+	Kitten,
+	/// Only adults can breed
+	Adult,
+	Elder,
+	/// An elder past the lifespan. Cannot breed, but can still be traded.
+	Legendary,
+}
+
+/// How the DNA of a kitty shows at its stage of life
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Appearance {
+	pub stage: LifeStage,
+	/// Fades by half for elders and legendary kitties
+	pub body_color: u8,
+	pub eye_color: u8,
+	/// Kittens have not grown their pattern yet
+	pub pattern: Option<u8>,
+	/// Kittens are half size
+	pub size: u8,
+	/// Only legendary kitties glow
+	pub glow: bool,
+}
+
+impl Appearance {
+	pub fn from_dna(dna: &[u8; 16], stage: LifeStage) -> Self {
+		let (body_color, eye_color, pattern, size) = (dna[5], dna[6], dna[7], dna[8]);
+		let faded = stage == LifeStage::Elder || stage == LifeStage::Legendary;
+		Appearance {
+			stage,
+			body_color: if faded { body_color / 2 } else { body_color },
+			eye_color,
+			pattern: if stage == LifeStage::Kitten { None } else { Some(pattern) },
+			size: if stage == LifeStage::Kitten { size / 2 } else { size },
+			glow: stage == LifeStage::Legendary,
+		}
+	}
 }
 
-type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type BalanceOf<T, I> = <<T as Trait<I>>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T, I> = <<T as Trait<I>>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
 
 #[derive(Encode, Decode)]
 pub struct Kitty(pub [u8; 16]);
 
-type KittyLinkedItem<T> = LinkedItem<<T as Trait>::KittyIndex>;
-type OwnedKittiesList<T> = LinkedList<OwnedKitties<T>, <T as system::Trait>::AccountId, <T as Trait>::KittyIndex>;
+pub type EggId = u64;
+
+/// A bred kitty waiting to hatch. The parents are locked until then.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode, Clone)]
+pub struct Egg<AccountId, KittyIndex, BlockNumber> {
+	/// Gets the kitty when it hatches
+	pub owner: AccountId,
+	pub parents: (KittyIndex, KittyIndex),
+	/// The DNA of the kitty, mixed from the parents when the egg is laid
+	pub dna: [u8; 16],
+	/// The generation of the kitty
+	pub generation: u32,
+	/// The block in which the egg hatches
+	pub hatch_at: BlockNumber,
+}
+
+/// A discrepancy between the kitties storage items, found by `Module::check_integrity`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum IntegrityError<AccountId, KittyIndex> {
+	/// A kitty id below `KittiesCount` has no kitty
+	MissingKitty(KittyIndex),
+	/// A kitty is stored at `KittiesCount`
+	KittyBeyondCount(KittyIndex),
+	/// A kitty has no owner and is not in the shelter
+	Unowned(KittyIndex),
+	/// A kitty has an owner and is also in the shelter
+	OwnedAndSheltered(KittyIndex),
+	/// A kitty is missing from the `OwnedKitties` list of its owner. (owner, kitty_id)
+	NotInOwnerList(AccountId, KittyIndex),
+	/// A kitty is in the `OwnedKitties` list of an account that does not own it. (account, kitty_id)
+	OwnerMismatch(AccountId, KittyIndex),
+	/// A kitty without an owner has a price
+	PriceWithoutOwner(KittyIndex),
+	/// A kitty with a price is missing from the listings
+	NotListed(KittyIndex),
+	/// A kitty in the listings has no price
+	ListedWithoutPrice(KittyIndex),
+	/// The `OwnedKitties` list of an account has broken links
+	BrokenOwnerList(AccountId, LinkError<KittyIndex>),
+	/// The shelter list has broken links
+	BrokenShelter(LinkError<KittyIndex>),
+	/// The listings have broken links or are out of price order
+	BrokenListings(LinkError<KittyIndex>),
+}
+
+/// The right of an account to breed a kitty it does not own
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode, Clone)]
+pub struct BreedingDelegation<AccountId, BlockNumber> {
+	pub delegate: AccountId,
+	/// The last block the delegate can breed in
+	pub expires_at: BlockNumber,
+	/// Number of times the delegate can still breed the kitty
+	pub uses_left: u32,
+	/// Whether the offspring goes to the delegate instead of the owner
+	pub offspring_to_delegate: bool,
+}
+
+/// The most accounts that can co-own a kitty
+pub const MAX_CO_OWNERS: u32 = 16;
+
+/// The accounts sharing a kitty. The kitty is in the `OwnedKitties` list of each of them,
+/// while `KittyOwners` holds the one that pays for its name and for its breeding.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct CoOwnership<AccountId> {
+	/// The co-owners and their shares of the sale proceeds
+	pub owners: Vec<(AccountId, u32)>,
+	/// Number of co-owners that must approve a proposal
+	pub threshold: u32,
+}
+
+/// What the co-owners of a kitty can do once enough of them approve
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum CoOwnedAction<AccountId, KittyIndex, Balance> {
+	/// Give the kitty to a single owner, ending the co-ownership
+	Transfer(AccountId),
+	/// Set the price of the kitty, or delist it with None. Any `buy` at the price is accepted.
+	/// The listing is placed from the hint kitty, see `ask`.
+	Ask(Option<Balance>, Option<KittyIndex>),
+	/// Breed with another kitty of the same co-owners. The owner in `KittyOwners` pays and gets the egg.
+	Breed(KittyIndex),
+}
+
+/// An action waiting for the approval of the co-owners of a kitty
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct CoOwnerProposal<AccountId, KittyIndex, Balance> {
+	pub action: CoOwnedAction<AccountId, KittyIndex, Balance>,
+	/// The co-owners that approved, the proposer first
+	pub approvals: Vec<AccountId>,
+}
+
+type KittyLinkedItem<T, I = DefaultInstance> = LinkedItem<<T as Trait<I>>::KittyIndex>;
+type CoOwnedActionOf<T, I = DefaultInstance> =
+	CoOwnedAction<<T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex, BalanceOf<T, I>>;
+type CoOwnerProposalOf<T, I = DefaultInstance> =
+	CoOwnerProposal<<T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex, BalanceOf<T, I>>;
+type BreedingDelegationOf<T> = BreedingDelegation<<T as system::Trait>::AccountId, <T as system::Trait>::BlockNumber>;
+type EggOf<T, I = DefaultInstance> =
+	Egg<<T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex, <T as system::Trait>::BlockNumber>;
+type OwnedKittiesList<T, I = DefaultInstance> =
+	LinkedList<OwnedKitties<T, I>, OwnedKittiesCount<T, I>, <T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex>;
+type ShelterList<T, I = DefaultInstance> = LinkedList<Shelter<T, I>, ShelterCount<I>, (), <T as Trait<I>>::KittyIndex>;
+type ListingsList<T, I = DefaultInstance> = SortedLinkedList<
+	Listings<T, I>, ListingsCount<I>, KittyPrices<T, I>, (), <T as Trait<I>>::KittyIndex, BalanceOf<T, I>, Ascending
+>;
 
 decl_storage! {
-	trait Store for Module<T: Trait> as Kitties {
+	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as Kitties {
 		/// Stores all the kitties, key is the kitty id / index
 		pub Kitties get(kitty): map T::KittyIndex => Option<Kitty>;
 		/// Stores the total number of kitties. i.e. the next kitty index
 		pub KittiesCount get(kitties_count): T::KittyIndex;
-
-		pub OwnedKitties get(owned_kitties): map (T::AccountId, Option<T::KittyIndex>) => Option<KittyLinkedItem<T>>;
+		/// Number of kitties created with `create`
+		pub Gen0Count get(gen0_count): u32;
+		/// Get kitty generation. Created kitties are generation 0, bred kitties one more than their older parent.
+		pub KittyGenerations get(kitty_generation): map T::KittyIndex => u32;
+		/// The block a kitty was born in. Use `kitty_age`, which covers kitties born before ageing was tracked.
+		pub KittyBirths: map T::KittyIndex => Option<T::BlockNumber>;
+		/// The first block run with ageing. Kitties born before have no birth block and count as adults in it.
+		pub AgeingSince get(ageing_since): Option<T::BlockNumber>;
+
+		pub OwnedKitties get(owned_kitties): map (T::AccountId, Option<T::KittyIndex>) => Option<KittyLinkedItem<T, I>>;
+		/// Number of kitties of an account. None for accounts whose kitties were not counted yet.
+		OwnedKittiesCount: map T::AccountId => Option<u32>;
+		/// Whether all lists have a recorded length. Until then `on_initialize` records them, kitty by kitty.
+		pub LengthsRecorded get(lengths_recorded): bool;
+		/// The next kitty whose owners get the length of their list recorded
+		NextLengthRecord: T::KittyIndex;
 
 		/// Get kitty owner
 		pub KittyOwners get(kitty_owner): map T::KittyIndex => Option<T::AccountId>;
 		/// Get kitty price. None means not for sale.
-		pub KittyPrices get(kitty_price): map T::KittyIndex => Option<BalanceOf<T>>
+		pub KittyPrices get(kitty_price): map T::KittyIndex => Option<BalanceOf<T, I>>;
+		/// Kitties for sale ordered by price, cheapest first
+		pub Listings get(listing): map ((), Option<T::KittyIndex>) => Option<KittyLinkedItem<T, I>>;
+		ListingsCount: map () => Option<u32>;
+
+		/// Whether an account must accept incoming transfers before it receives the kitty
+		pub TransferApprovalRequired get(transfer_approval_required): map T::AccountId => bool;
+		/// Get the recipient of a pending transfer. The kitty stays with its owner until resolved.
+		pub PendingTransfers get(pending_transfer): map T::KittyIndex => Option<T::AccountId>;
+
+		/// Kitties of reaped accounts waiting to be adopted. They have no owner.
+		pub Shelter get(shelter): map ((), Option<T::KittyIndex>) => Option<KittyLinkedItem<T, I>>;
+		ShelterCount: map () => Option<u32>;
+
+		/// Frozen kitties cannot be transferred, bred or listed
+		pub FrozenKitties get(is_frozen): map T::KittyIndex => bool;
+		/// Kitties locked by another module through `NonFungibleAsset`. They cannot be transferred, bred or listed.
+		pub LockedKitties get(is_locked): map T::KittyIndex => bool;
+		/// Locked kitties left with a reaped owner. Egg parents go to the shelter when the egg unlocks them.
+		pub StrandedKitties get(is_stranded): map T::KittyIndex => bool;
+		/// Whether `ask` and `buy` are paused
+		pub MarketPaused get(market_paused): bool;
+		/// The lowest price a kitty can be listed for
+		pub MinimumPrice get(minimum_price): BalanceOf<T, I>;
+		/// The most kitties an account can own, set by root. None means `MaxKittiesPerAccount`.
+		pub KittiesPerAccountLimit get(kitties_per_account_limit): Option<u32>;
+		/// The breeding fees (base, per kitty, per generation) set by root. None means the fees of `Trait`.
+		pub BreedingFees get(breeding_fees): Option<(BalanceOf<T, I>, BalanceOf<T, I>, BalanceOf<T, I>)>;
+
+		/// Get kitty name
+		pub KittyNames get(kitty_name): map T::KittyIndex => Option<Vec<u8>>;
+		/// The deposit reserved from the owner for the name of a kitty
+		pub NameDeposits get(name_deposit): map T::KittyIndex => BalanceOf<T, I>;
+		/// Get the kitty with a name. Names are unique across all kitties.
+		pub NamedKitties get(named_kitty): map Vec<u8> => Option<T::KittyIndex>;
+
+		/// Get egg. Eggs stay here until they hatch.
+		pub Eggs get(egg): map EggId => Option<EggOf<T, I>>;
+		/// The id of the next egg
+		pub NextEggId get(next_egg_id): EggId;
+		/// Get the eggs of an account. They count towards `MaxKittiesPerAccount`.
+		pub OwnedEggs get(eggs_of): map T::AccountId => Vec<EggId>;
+		/// Get the eggs hatching in a block
+		pub EggsHatchingAt get(eggs_hatching_at): map T::BlockNumber => Vec<EggId>;
+
+		/// Get the breeding delegation of a kitty. Cleared when the kitty changes owner.
+		pub BreedingDelegations get(breeding_delegation): map T::KittyIndex => Option<BreedingDelegationOf<T>>;
+
+		/// Get the co-owners of a kitty. None if it has a single owner.
+		pub CoOwners get(co_owners): map T::KittyIndex => Option<CoOwnership<T::AccountId>>;
+		/// Get the open proposal of the co-owners of a kitty
+		pub CoOwnerProposals get(co_owner_proposal): map T::KittyIndex => Option<CoOwnerProposalOf<T, I>>;
 	}
 }
 
 decl_event!(
-	pub enum Event<T> where
+	pub enum Event<T, I: Instance = DefaultInstance> where
 		<T as system::Trait>::AccountId,
-		<T as Trait>::KittyIndex,
-		Balance = BalanceOf<T>,
+		<T as system::Trait>::BlockNumber,
+		<T as Trait<I>>::KittyIndex,
+		Balance = BalanceOf<T, I>,
 	{
 		/// A kitty is created. (owner, kitty_id)
 		Created(AccountId, KittyIndex),
@@ -53,85 +386,526 @@ decl_event!(
 		Ask(AccountId, KittyIndex, Option<Balance>),
 		/// A kitty is sold. (from, to, kitty_id, price)
 		Sold(AccountId, AccountId, KittyIndex, Balance),
+		/// An account changed its transfer approval setting. (who, required)
+		TransferApprovalSet(AccountId, bool),
+		/// A transfer is waiting for the recipient to accept. (from, to, kitty_id)
+		TransferRequested(AccountId, AccountId, KittyIndex),
+		/// A pending transfer is rejected by the recipient. (from, to, kitty_id)
+		TransferRejected(AccountId, AccountId, KittyIndex),
+		/// A pending transfer is cancelled by the sender. (from, to, kitty_id)
+		TransferCancelled(AccountId, AccountId, KittyIndex),
+		/// A kitty of a reaped account is moved to the shelter. (previous_owner, kitty_id)
+		Sheltered(AccountId, KittyIndex),
+		/// A kitty of a reaped account cannot be sheltered and stays with its owner. (owner, kitty_id)
+		NotSheltered(AccountId, KittyIndex),
+		/// A kitty is adopted from the shelter. (owner, kitty_id)
+		Adopted(AccountId, KittyIndex),
+		/// A kitty is moved within the owner's list. (owner, kitty_id, after)
+		Reordered(AccountId, KittyIndex, Option<KittyIndex>),
+		/// A kitty is frozen by root. (kitty_id)
+		Frozen(KittyIndex),
+		/// A kitty is unfrozen by root. (kitty_id)
+		Unfrozen(KittyIndex),
+		/// A kitty is locked through `NonFungibleAsset`. (kitty_id)
+		Locked(KittyIndex),
+		/// A kitty is unlocked through `NonFungibleAsset`. (kitty_id)
+		Unlocked(KittyIndex),
+		/// The marketplace is paused or resumed by root. (paused)
+		MarketPauseSet(bool),
+		/// The minimum listing price is changed by root. (price)
+		MinimumPriceSet(Balance),
+		/// The most kitties an account can own is changed by root. (max)
+		MaxKittiesPerAccountSet(u32),
+		/// The breeding fees are changed by root. (base, per_kitty, per_generation)
+		BreedingFeesSet(Balance, Balance, Balance),
+		/// A kitty is named. (owner, kitty_id, name)
+		Named(AccountId, KittyIndex, Vec<u8>),
+		/// The name of a kitty is cleared and its deposit returned. (owner, kitty_id)
+		NameCleared(AccountId, KittyIndex),
+		/// Breeding laid an egg. (owner, egg_id, hatch_at)
+		EggLaid(AccountId, EggId, BlockNumber),
+		/// An egg is transferred. (from, to, egg_id)
+		EggTransferred(AccountId, AccountId, EggId),
+		/// An egg hatched into a kitty. (owner, egg_id, kitty_id)
+		Hatched(AccountId, EggId, KittyIndex),
+		/// An egg failed to hatch `MAX_HATCH_ATTEMPTS` times and is discarded. (owner, egg_id)
+		EggDiscarded(AccountId, EggId),
+		/// An owner lets another account breed a kitty. (owner, kitty_id, delegate, expires_at, uses)
+		BreedingDelegated(AccountId, KittyIndex, AccountId, BlockNumber, u32),
+		/// An owner revokes the breeding delegation of a kitty. (owner, kitty_id)
+		BreedingDelegationRevoked(AccountId, KittyIndex),
+		/// An owner shares a kitty. (owner, kitty_id, threshold)
+		CoOwned(AccountId, KittyIndex, u32),
+		/// A co-owner proposes an action, replacing any open proposal. (proposer, kitty_id)
+		CoOwnerProposed(AccountId, KittyIndex),
+		/// A co-owner approves the open proposal. (co_owner, kitty_id, approvals)
+		CoOwnerApproved(AccountId, KittyIndex, u32),
+		/// The open proposal reached the threshold and was executed. (kitty_id)
+		CoOwnerProposalExecuted(KittyIndex),
 	}
 );
 
 decl_module! {
-	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
-		fn deposit_event() = default;
+	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		fn deposit_event<T, I>() = default;
+
+		fn on_initialize(now: T::BlockNumber) {
+			if Self::ageing_since().is_none() {
+				<AgeingSince<T, I>>::put(now);
+			}
+			if !Self::lengths_recorded() {
+				Self::record_lengths();
+			}
+			Self::hatch_eggs(now);
+		}
 
 		/// Create a new kitty
 		pub fn create(origin) {
 			let sender = ensure_signed(origin)?;
 			let kitty_id = Self::next_kitty_id()?;
+			let gen0_count = Self::gen0_count();
+			ensure!(gen0_count < T::MaxGen0Kitties::get(), "Gen-0 population limit reached");
 
 			// Generate a random 128bit value
 			let dna = Self::random_value(&sender);
 
 			// Create and store kitty
 			let kitty = Kitty(dna);
-			Self::insert_kitty(&sender, kitty_id, kitty);
+			Self::insert_kitty(&sender, kitty_id, kitty)?;
+			<Gen0Count<I>>::put(gen0_count + 1);
 
 			Self::deposit_event(RawEvent::Created(sender, kitty_id));
 		}
 
-		/// Breed kitties
+		/// Breed kitties, paying the breeding fee. See `Module::breeding_fee`.
+		/// Breeding lays an egg that hatches after `HatchingPeriod`, the parents are locked until then.
+		/// A breeding delegate of both kitties can breed them for their owner.
 		pub fn breed(origin, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) {
 			let sender = ensure_signed(origin)?;
 
-			let new_kitty_id = Self::do_breed(&sender, kitty_id_1, kitty_id_2)?;
+			ensure!(!Self::is_co_owned(kitty_id_1) && !Self::is_co_owned(kitty_id_2), "Kitty is co-owned");
+			let (egg_owner, egg_id, hatch_at) = Self::do_breed(&sender, kitty_id_1, kitty_id_2)?;
+
+			Self::deposit_event(RawEvent::EggLaid(egg_owner, egg_id, hatch_at));
+		}
+
+		/// Let `delegate` breed a kitty up to `uses` times until block `expires_at`.
+		/// Replaces any previous delegation of the kitty.
+		pub fn delegate_breeding(origin, kitty_id: T::KittyIndex, delegate: <T::Lookup as StaticLookup>::Source, expires_at: T::BlockNumber, uses: u32, offspring_to_delegate: bool) {
+			let sender = ensure_signed(origin)?;
+			let delegate = T::Lookup::lookup(delegate)?;
+
+			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can delegate breeding");
+			ensure!(!Self::is_co_owned(kitty_id), "Kitty is co-owned");
+			ensure!(sender != delegate, "Cannot delegate breeding to yourself");
+			ensure!(uses > 0, "Delegation has no uses");
+			ensure!(expires_at >= <system::Module<T>>::block_number(), "Delegation is already expired");
+
+			<BreedingDelegations<T, I>>::insert(kitty_id, BreedingDelegation {
+				delegate: delegate.clone(),
+				expires_at,
+				uses_left: uses,
+				offspring_to_delegate,
+			});
+
+			Self::deposit_event(RawEvent::BreedingDelegated(sender, kitty_id, delegate, expires_at, uses));
+		}
+
+		/// Revoke the breeding delegation of a kitty
+		pub fn revoke_breeding_delegation(origin, kitty_id: T::KittyIndex) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can delegate breeding");
+			ensure!(<BreedingDelegations<T, I>>::exists(kitty_id), "Kitty has no breeding delegation");
+
+			<BreedingDelegations<T, I>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::BreedingDelegationRevoked(sender, kitty_id));
+		}
+
+		/// Transfer an egg to a new owner, who gets the kitty when it hatches
+		pub fn transfer_egg(origin, to: <T::Lookup as StaticLookup>::Source, egg_id: EggId) {
+			let sender = ensure_signed(origin)?;
+			let to = T::Lookup::lookup(to)?;
+
+			let mut egg = Self::egg(egg_id).ok_or("Egg does not exist")?;
+			ensure!(egg.owner == sender, "Only owner can transfer egg");
+			ensure!(sender != to, "Cannot transfer egg to yourself");
+			Self::ensure_can_receive(&to)?;
 
-			Self::deposit_event(RawEvent::Created(sender, new_kitty_id));
+			<OwnedEggs<T, I>>::mutate(&sender, |eggs| eggs.retain(|id| *id != egg_id));
+			<OwnedEggs<T, I>>::mutate(&to, |eggs| eggs.push(egg_id));
+			egg.owner = to.clone();
+			<Eggs<T, I>>::insert(egg_id, egg);
+
+			Self::deposit_event(RawEvent::EggTransferred(sender, to, egg_id));
 		}
 
 		/// Transfer a kitty to new owner
- 		pub fn transfer(origin, to: T::AccountId, kitty_id: T::KittyIndex) {
+		/// If the recipient requires approval, the transfer stays pending until it is accepted
+ 		pub fn transfer(origin, to: <T::Lookup as StaticLookup>::Source, kitty_id: T::KittyIndex) {
  			let sender = ensure_signed(origin)?;
+			let to = T::Lookup::lookup(to)?;
+
+  			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can transfer kitty");
+			ensure!(sender != to, "Cannot transfer kitty to yourself");
+			ensure!(!Self::is_co_owned(kitty_id), "Kitty is co-owned");
+			ensure!(!Self::is_frozen(kitty_id), "Kitty is frozen");
+			ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+			ensure!(!<PendingTransfers<T, I>>::exists(kitty_id), "Kitty has a pending transfer");
+
+			if Self::transfer_approval_required(&to) {
+				<PendingTransfers<T, I>>::insert(kitty_id, &to);
+
+				Self::deposit_event(RawEvent::TransferRequested(sender, to, kitty_id));
+			} else {
+				Self::do_transfer(&sender, &to, kitty_id, None)?;
+
+				Self::deposit_event(RawEvent::Transferred(sender, to, kitty_id));
+			}
+		}
+
+		/// Require or stop requiring approval for incoming transfers
+		pub fn set_transfer_approval(origin, required: bool) {
+			let sender = ensure_signed(origin)?;
+
+			if required {
+				<TransferApprovalRequired<T, I>>::insert(&sender, true);
+			} else {
+				<TransferApprovalRequired<T, I>>::remove(&sender);
+			}
+
+			Self::deposit_event(RawEvent::TransferApprovalSet(sender, required));
+		}
+
+		/// Accept a pending transfer sent to you
+		pub fn accept_transfer(origin, kitty_id: T::KittyIndex) {
+			let sender = ensure_signed(origin)?;
+
+			let owner = Self::ensure_pending_recipient(&sender, kitty_id)?;
+			ensure!(!Self::is_frozen(kitty_id), "Kitty is frozen");
+
+			Self::do_transfer(&owner, &sender, kitty_id, None)?;
+
+			<PendingTransfers<T, I>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::Transferred(owner, sender, kitty_id));
+		}
+
+		/// Reject a pending transfer sent to you
+		pub fn reject_transfer(origin, kitty_id: T::KittyIndex) {
+			let sender = ensure_signed(origin)?;
 
-  			ensure!(<OwnedKitties<T>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can transfer kitty");
+			let owner = Self::ensure_pending_recipient(&sender, kitty_id)?;
 
-			Self::do_transfer(&sender, &to, kitty_id);
+			<PendingTransfers<T, I>>::remove(kitty_id);
 
-			Self::deposit_event(RawEvent::Transferred(sender, to, kitty_id));
+			Self::deposit_event(RawEvent::TransferRejected(owner, sender, kitty_id));
+		}
+
+		/// Cancel a pending transfer of your kitty
+		pub fn cancel_transfer(origin, kitty_id: T::KittyIndex) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can cancel transfer");
+			let to = Self::pending_transfer(kitty_id).ok_or("No pending transfer")?;
+
+			<PendingTransfers<T, I>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::TransferCancelled(sender, to, kitty_id));
 		}
 
 		/// Set a price for a kitty for sale
 		/// None to delist the kitty
-		pub fn ask(origin, kitty_id: T::KittyIndex, price: Option<BalanceOf<T>>) {
+		/// `hint` is a listed kitty at or below the price to look for the place of the listing from,
+		/// at most `MAX_LISTING_STEPS` listings away. None looks from the cheapest listing.
+		pub fn ask(origin, kitty_id: T::KittyIndex, price: Option<BalanceOf<T, I>>, hint: Option<T::KittyIndex>) {
 			let sender = ensure_signed(origin)?;
 
-			ensure!(<OwnedKitties<T>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can set price for kitty");
+			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can set price for kitty");
+			ensure!(!Self::is_co_owned(kitty_id), "Kitty is co-owned");
+			ensure!(!<PendingTransfers<T, I>>::exists(kitty_id), "Kitty has a pending transfer");
 
-			if let Some(ref price) = price {
-				<KittyPrices<T>>::insert(kitty_id, price);
-			} else {
-				<KittyPrices<T>>::remove(kitty_id);
-			}
+			Self::set_price(kitty_id, price, hint)?;
 
 			Self::deposit_event(RawEvent::Ask(sender, kitty_id, price));
 		}
 
-		pub fn buy(origin, kitty_id: T::KittyIndex, price: BalanceOf<T>) {
+		pub fn buy(origin, kitty_id: T::KittyIndex, price: BalanceOf<T, I>) {
 			let sender = ensure_signed(origin)?;
 
+			ensure!(!Self::market_paused(), "Market is paused");
+
 			let owner = Self::kitty_owner(kitty_id);
 			ensure!(owner.is_some(), "Kitty does not exist");
 			let owner = owner.unwrap();
 
 			let kitty_price = Self::kitty_price(kitty_id);
 			ensure!(kitty_price.is_some(), "Kitty not for sale");
+			ensure!(!<PendingTransfers<T, I>>::exists(kitty_id), "Kitty has a pending transfer");
 
 			let kitty_price = kitty_price.unwrap();
 			ensure!(price >= kitty_price, "Price is too low");
+			ensure!(owner != sender, "Cannot buy your own kitty");
+			ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+			// Check before paying, `do_transfer` would only fail after the payment
+			Self::ensure_can_receive(&sender)?;
+
+			if let Some(co_ownership) = Self::co_owners(kitty_id) {
+				ensure!(!Self::is_co_owner(&co_ownership, &sender), "Cannot buy your own kitty");
 
-			T::Currency::transfer(&sender, &owner, kitty_price)?;
+				Self::pay_co_owners(&sender, &co_ownership, kitty_price)?;
 
-			<KittyPrices<T>>::remove(kitty_id);
+				Self::give_co_owned(kitty_id, &co_ownership, &sender, Some(kitty_price))?;
+			} else {
+				// Keep the buyer alive, the kitty would be lost with a reaped account
+				let payment = T::Currency::withdraw(&sender, kitty_price, WithdrawReason::Transfer, ExistenceRequirement::KeepAlive)?;
+				T::Currency::resolve_creating(&owner, payment);
 
-			Self::do_transfer(&owner, &sender, kitty_id);
+				Self::do_transfer(&owner, &sender, kitty_id, Some(kitty_price))?;
+			}
 
 			Self::deposit_event(RawEvent::Sold(owner, sender, kitty_id, kitty_price));
 		}
+
+		/// Move a kitty in your list right after `after`, or to the front if `after` is None
+		pub fn reorder(origin, kitty_id: T::KittyIndex, after: Option<T::KittyIndex>) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can reorder kitty");
+
+			if let Some(after) = after {
+				ensure!(after != kitty_id, "Cannot move kitty after itself");
+				ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(after))), "Only owner can reorder kitty");
+
+				<OwnedKittiesList<T, I>>::move_after(&sender, after, kitty_id)?;
+			} else {
+				<OwnedKittiesList<T, I>>::move_to_front(&sender, kitty_id)?;
+			}
+
+			Self::deposit_event(RawEvent::Reordered(sender, kitty_id, after));
+		}
+
+		/// Adopt a kitty from the shelter
+		pub fn adopt(origin, kitty_id: T::KittyIndex) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(<Shelter<T, I>>::exists(&((), Some(kitty_id))), "Kitty is not in the shelter");
+			ensure!(!Self::is_frozen(kitty_id), "Kitty is frozen");
+			ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+			Self::ensure_can_receive(&sender)?;
+
+			Self::insert_owned_kitty(&sender, kitty_id)?;
+			<ShelterList<T, I>>::remove(&(), kitty_id)?;
+			<KittyOwners<T, I>>::insert(kitty_id, &sender);
+
+			T::OnKittyTransferred::on_kitty_transferred(None, &sender, kitty_id, None);
+
+			Self::deposit_event(RawEvent::Adopted(sender, kitty_id));
+		}
+
+		/// Name a kitty, reserving a deposit for each byte of the name
+		/// Renaming adjusts the deposit to the new name
+		pub fn set_name(origin, kitty_id: T::KittyIndex, name: Vec<u8>) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can name kitty");
+			ensure!(!Self::is_co_owned(kitty_id), "Kitty is co-owned");
+			ensure!(!name.is_empty(), "Name is empty");
+			ensure!(name.len() as u32 <= T::MaxNameLength::get(), "Name is too long");
+			ensure!(core::str::from_utf8(&name).is_ok(), "Name is not valid UTF-8");
+			ensure!(!<NamedKitties<T, I>>::exists(&name), "Name is already taken");
+
+			let deposit = T::NameByteDeposit::get().saturating_mul((name.len() as u32).into());
+			let old_deposit = Self::name_deposit(kitty_id);
+			if deposit > old_deposit {
+				T::Currency::reserve(&sender, deposit - old_deposit)?;
+			} else {
+				T::Currency::unreserve(&sender, old_deposit - deposit);
+			}
+
+			if let Some(old_name) = <KittyNames<T, I>>::take(kitty_id) {
+				<NamedKitties<T, I>>::remove(&old_name);
+			}
+			<KittyNames<T, I>>::insert(kitty_id, &name);
+			<NameDeposits<T, I>>::insert(kitty_id, deposit);
+			<NamedKitties<T, I>>::insert(&name, kitty_id);
+
+			Self::deposit_event(RawEvent::Named(sender, kitty_id, name));
+		}
+
+		/// Clear the name of a kitty and get its deposit back
+		pub fn clear_name(origin, kitty_id: T::KittyIndex) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can name kitty");
+			// The deposit of a co-owned kitty is held by the lead co-owner until it leaves
+			ensure!(!Self::is_co_owned(kitty_id), "Kitty is co-owned");
+			ensure!(<KittyNames<T, I>>::exists(kitty_id), "Kitty has no name");
+
+			T::Currency::unreserve(&sender, Self::take_name(kitty_id));
+
+			Self::deposit_event(RawEvent::NameCleared(sender, kitty_id));
+		}
+
+		/// Share your kitty with `co_owners`, which must include you. Shares split the sale proceeds,
+		/// `threshold` co-owners must approve each `CoOwnedAction`.
+		pub fn co_own(origin, kitty_id: T::KittyIndex, co_owners: Vec<(T::AccountId, u32)>, threshold: u32) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(<OwnedKitties<T, I>>::exists(&(sender.clone(), Some(kitty_id))), "Only owner can share kitty");
+			ensure!(!Self::is_co_owned(kitty_id), "Kitty is co-owned");
+			ensure!(!Self::is_frozen(kitty_id), "Kitty is frozen");
+			ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+			ensure!(!<PendingTransfers<T, I>>::exists(kitty_id), "Kitty has a pending transfer");
+			ensure!(co_owners.len() >= 2 && co_owners.len() as u32 <= MAX_CO_OWNERS, "Invalid number of co-owners");
+			ensure!(co_owners.iter().any(|(who, _)| *who == sender), "Owner must be a co-owner");
+			ensure!(co_owners.iter().all(|(_, share)| *share > 0), "Share is zero");
+			ensure!(threshold > 0 && threshold <= co_owners.len() as u32, "Invalid threshold");
+			let mut unique = BTreeSet::new();
+			for (who, _) in co_owners.iter() {
+				ensure!(unique.insert(who), "Duplicate co-owner");
+				if *who != sender {
+					Self::ensure_can_receive(who)?;
+				}
+			}
+
+			for (who, _) in co_owners.iter() {
+				if *who != sender {
+					Self::insert_owned_kitty(who, kitty_id)?;
+				}
+			}
+			// The price and the breeding delegation were set by the single owner
+			Self::delist_kitty(kitty_id);
+			<BreedingDelegations<T, I>>::remove(kitty_id);
+			for (who, _) in co_owners.iter() {
+				if *who != sender {
+					T::OnKittyTransferred::on_kitty_transferred(Some(&sender), who, kitty_id, None);
+				}
+			}
+			<CoOwners<T, I>>::insert(kitty_id, CoOwnership { owners: co_owners, threshold });
+
+			Self::deposit_event(RawEvent::CoOwned(sender, kitty_id, threshold));
+		}
+
+		/// Propose an action for a co-owned kitty, replacing any open proposal.
+		/// Counts as the approval of the proposer.
+		pub fn propose_co_owned(origin, kitty_id: T::KittyIndex, action: CoOwnedActionOf<T, I>) {
+			let sender = ensure_signed(origin)?;
+
+			let co_ownership = Self::co_owners(kitty_id).ok_or("Kitty is not co-owned")?;
+			ensure!(Self::is_co_owner(&co_ownership, &sender), "Only co-owner can propose");
+
+			let proposal = CoOwnerProposal { action, approvals: vec![sender.clone()] };
+			let executed = Self::settle_proposal(kitty_id, &co_ownership, proposal)?;
+
+			Self::deposit_event(RawEvent::CoOwnerProposed(sender, kitty_id));
+			if executed {
+				Self::deposit_event(RawEvent::CoOwnerProposalExecuted(kitty_id));
+			}
+		}
+
+		/// Approve the open proposal of a co-owned kitty, executing it once it has enough approvals
+		pub fn approve_co_owned(origin, kitty_id: T::KittyIndex) {
+			let sender = ensure_signed(origin)?;
+
+			let co_ownership = Self::co_owners(kitty_id).ok_or("Kitty is not co-owned")?;
+			ensure!(Self::is_co_owner(&co_ownership, &sender), "Only co-owner can approve");
+			let mut proposal = Self::co_owner_proposal(kitty_id).ok_or("No open proposal")?;
+			ensure!(!proposal.approvals.contains(&sender), "Already approved");
+
+			proposal.approvals.push(sender.clone());
+			let approvals = proposal.approvals.len() as u32;
+			let executed = Self::settle_proposal(kitty_id, &co_ownership, proposal)?;
+
+			Self::deposit_event(RawEvent::CoOwnerApproved(sender, kitty_id, approvals));
+			if executed {
+				Self::deposit_event(RawEvent::CoOwnerProposalExecuted(kitty_id));
+			}
+		}
+
+		/// Move a kitty to a new owner, bypassing the owner and any approval setting.
+		/// A co-owned kitty leaves its co-owners. Locked kitties cannot be moved, as the loan, vault or egg
+		/// holding the lock would lose track of them. The module holding the lock has to release them first.
+		pub fn force_transfer(origin, to: <T::Lookup as StaticLookup>::Source, kitty_id: T::KittyIndex) {
+			ensure_root(origin)?;
+			let to = T::Lookup::lookup(to)?;
+
+			let owner = Self::kitty_owner(kitty_id).ok_or("Kitty does not exist")?;
+			ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+
+			if let Some(co_ownership) = Self::co_owners(kitty_id) {
+				if !Self::is_co_owner(&co_ownership, &to) {
+					Self::ensure_can_receive(&to)?;
+				}
+				Self::give_co_owned(kitty_id, &co_ownership, &to, None)?;
+			} else {
+				Self::do_transfer(&owner, &to, kitty_id, None)?;
+			}
+
+			<PendingTransfers<T, I>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::Transferred(owner, to, kitty_id));
+		}
+
+		/// Freeze a kitty and remove it from the market
+		pub fn freeze(origin, kitty_id: T::KittyIndex) {
+			ensure_root(origin)?;
+
+			ensure!(<Kitties<T, I>>::exists(kitty_id), "Kitty does not exist");
+
+			<FrozenKitties<T, I>>::insert(kitty_id, true);
+			Self::delist_kitty(kitty_id);
+
+			Self::deposit_event(RawEvent::Frozen(kitty_id));
+		}
+
+		/// Unfreeze a kitty
+		pub fn unfreeze(origin, kitty_id: T::KittyIndex) {
+			ensure_root(origin)?;
+
+			ensure!(Self::is_frozen(kitty_id), "Kitty is not frozen");
+
+			<FrozenKitties<T, I>>::remove(kitty_id);
+
+			Self::deposit_event(RawEvent::Unfrozen(kitty_id));
+		}
+
+		/// Pause or resume `ask` and `buy`
+		pub fn set_market_paused(origin, paused: bool) {
+			ensure_root(origin)?;
+
+			<MarketPaused<I>>::put(paused);
+
+			Self::deposit_event(RawEvent::MarketPauseSet(paused));
+		}
+
+		/// Set the lowest price a kitty can be listed for
+		pub fn set_minimum_price(origin, price: BalanceOf<T, I>) {
+			ensure_root(origin)?;
+
+			<MinimumPrice<T, I>>::put(price);
+
+			Self::deposit_event(RawEvent::MinimumPriceSet(price));
+		}
+
+		/// Set the most kitties an account can own. Accounts above the limit keep their kitties.
+		pub fn set_max_kitties_per_account(origin, max: u32) {
+			ensure_root(origin)?;
+
+			<KittiesPerAccountLimit<I>>::put(max);
+
+			Self::deposit_event(RawEvent::MaxKittiesPerAccountSet(max));
+		}
+
+		/// Set the breeding fees. See `Module::breeding_fee_for`.
+		pub fn set_breeding_fees(origin, base: BalanceOf<T, I>, per_kitty: BalanceOf<T, I>, per_generation: BalanceOf<T, I>) {
+			ensure_root(origin)?;
+
+			<BreedingFees<T, I>>::put((base, per_kitty, per_generation));
+
+			Self::deposit_event(RawEvent::BreedingFeesSet(base, per_kitty, per_generation));
+		}
 	}
 }
 
@@ -139,12 +913,18 @@ fn combine_dna(dna1: u8, dna2: u8, selector: u8) -> u8 {
 	((selector & dna1) | (!selector & dna2))
 }
 
-impl<T: Trait> Module<T> {
+impl<T: Trait<I>, I: Instance> Module<T, I> {
 	fn random_value(sender: &T::AccountId) -> [u8; 16] {
 		let payload = (<system::Module<T>>::random_seed(), sender, <system::Module<T>>::extrinsic_index(), <system::Module<T>>::block_number());
 		payload.using_encoded(blake2_128)
 	}
 
+	fn ensure_pending_recipient(who: &T::AccountId, kitty_id: T::KittyIndex) -> result::Result<T::AccountId, &'static str> {
+		let to = Self::pending_transfer(kitty_id).ok_or("No pending transfer")?;
+		ensure!(to == *who, "Not recipient of pending transfer");
+		Self::kitty_owner(kitty_id).ok_or("Kitty does not exist")
+	}
+
 	fn next_kitty_id() -> result::Result<T::KittyIndex, &'static str> {
 		let kitty_id = Self::kitties_count();
 		if kitty_id == T::KittyIndex::max_value() {
@@ -153,30 +933,274 @@ impl<T: Trait> Module<T> {
 		Ok(kitty_id)
 	}
 
-	fn insert_owned_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) {
-		<OwnedKittiesList<T>>::append(owner, kitty_id);
+	/// Ensure `who` can own one more kitty or egg
+	fn ensure_can_receive(who: &T::AccountId) -> result::Result<(), &'static str> {
+		let owned = <OwnedKittiesList<T, I>>::len(who).saturating_add(Self::eggs_of(who).len() as u32);
+		ensure!(owned < Self::max_kitties_per_account(), "Account has too many kitties");
+		Ok(())
+	}
+
+	fn insert_owned_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) -> result::Result<(), &'static str> {
+		<OwnedKittiesList<T, I>>::append(owner, kitty_id)?;
+		Self::update_reap_lock(owner);
+		Ok(())
+	}
+
+	fn remove_owned_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) -> result::Result<(), &'static str> {
+		<OwnedKittiesList<T, I>>::remove(owner, kitty_id)?;
+		Self::update_reap_lock(owner);
+		Ok(())
 	}
 
-	fn insert_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex, kitty: Kitty) {
-		// Create and store kitty
-		<Kitties<T>>::insert(kitty_id, kitty);
-		<KittiesCount<T>>::put(kitty_id + 1.into());
-		<KittyOwners<T>>::insert(kitty_id, owner.clone());
+	/// Keep the existential deposit of kitty owners locked under `ReapPolicy::BlockReaping`
+	fn update_reap_lock(owner: &T::AccountId) {
+		if T::KittyReapPolicy::get() != ReapPolicy::BlockReaping {
+			return;
+		}
+
+		if !<OwnedKittiesList<T, I>>::is_empty(owner) {
+			T::Currency::set_lock(
+				T::ReapLockId::get(),
+				owner,
+				T::Currency::minimum_balance(),
+				T::BlockNumber::max_value(),
+				WithdrawReasons::all(),
+			);
+		} else {
+			T::Currency::remove_lock(T::ReapLockId::get(), owner);
+		}
+	}
+
+	/// Set the price of a kitty and place it in the listings, looking for the place from `hint`
+	fn list_kitty(
+		kitty_id: T::KittyIndex,
+		price: BalanceOf<T, I>,
+		hint: Option<T::KittyIndex>,
+	) -> result::Result<(), &'static str> {
+		// Find the place before the kitty is delisted, so a failed search leaves the listing as it was
+		let after = <ListingsList<T, I>>::position(&(), kitty_id, &price, hint, MAX_LISTING_STEPS)?;
+		Self::delist_kitty(kitty_id);
+
+		<KittyPrices<T, I>>::insert(kitty_id, price);
+		<ListingsList<T, I>>::insert(&(), kitty_id, after, 0)
+	}
+
+	/// Remove the price of a kitty and take it out of the listings
+	fn delist_kitty(kitty_id: T::KittyIndex) {
+		<KittyPrices<T, I>>::remove(kitty_id);
+		// A kitty without a price is not in the listings, nothing to remove then
+		let _ = <ListingsList<T, I>>::remove(&(), kitty_id);
+	}
+
+	/// List a kitty at `price`, or delist it if `price` is None
+	fn set_price(
+		kitty_id: T::KittyIndex,
+		price: Option<BalanceOf<T, I>>,
+		hint: Option<T::KittyIndex>,
+	) -> result::Result<(), &'static str> {
+		if let Some(price) = price {
+			ensure!(!Self::market_paused(), "Market is paused");
+			ensure!(!Self::is_frozen(kitty_id), "Kitty is frozen");
+			ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+			ensure!(price >= Self::minimum_price(), "Price is below minimum");
+
+			Self::list_kitty(kitty_id, price, hint)
+		} else {
+			Self::delist_kitty(kitty_id);
+			Ok(())
+		}
+	}
+
+	/// Remove the name of a kitty from the registry, returning its deposit
+	fn take_name(kitty_id: T::KittyIndex) -> BalanceOf<T, I> {
+		if let Some(name) = <KittyNames<T, I>>::take(kitty_id) {
+			<NamedKitties<T, I>>::remove(&name);
+		}
+		<NameDeposits<T, I>>::take(kitty_id)
+	}
+
+	/// Move the name deposit of a kitty from `from` to `to`.
+	/// The name is released if `to` cannot cover the deposit, as the payment of a sale is already made.
+	fn transfer_name(from: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex) {
+		if !<KittyNames<T, I>>::exists(kitty_id) {
+			return;
+		}
+
+		let deposit = Self::name_deposit(kitty_id);
+		T::Currency::unreserve(from, deposit);
+		if T::Currency::reserve(to, deposit).is_err() {
+			Self::take_name(kitty_id);
+			Self::deposit_event(RawEvent::NameCleared(to.clone(), kitty_id));
+		}
+	}
+
+	/// Move all kitties of `who` to the shelter, releasing their names. The eggs of `who` hatch into the shelter.
+	/// Locked kitties and kitties that cannot be sheltered stay with `who`, together with the reap lock,
+	/// so the module holding the lock can still move them. Parents locked by an egg go to the shelter when it hatches.
+	fn shelter_kitties(who: &T::AccountId) {
+		// Hatch first, so parents of `who` unlocked by hatching are sheltered too
+		for egg_id in Self::eggs_of(who) {
+			if let Some(egg) = Self::egg(egg_id) {
+				let _ = Self::hatch_into_shelter(egg_id, &egg);
+			}
+		}
+		if Self::eggs_of(who).is_empty() {
+			<OwnedEggs<T, I>>::remove(who);
+		}
+
+		let mut name_deposits = BalanceOf::<T, I>::zero();
+		let kitties = <OwnedKittiesList<T, I>>::iter(who).collect::<Vec<_>>();
+		for kitty_id in kitties {
+			// The other co-owners keep the kitty
+			if let Some(co_ownership) = Self::co_owners(kitty_id) {
+				let _ = <OwnedKittiesList<T, I>>::remove(who, kitty_id);
+				name_deposits = name_deposits.saturating_add(Self::leave_co_ownership(who, kitty_id, co_ownership));
+				continue;
+			}
+			if Self::is_locked(kitty_id) {
+				<StrandedKitties<T, I>>::insert(kitty_id, true);
+				Self::deposit_event(RawEvent::NotSheltered(who.clone(), kitty_id));
+				continue;
+			}
+			match Self::shelter_kitty(who, kitty_id) {
+				Ok(name_deposit) => name_deposits = name_deposits.saturating_add(name_deposit),
+				Err(_) => Self::deposit_event(RawEvent::NotSheltered(who.clone(), kitty_id)),
+			}
+		}
+		Self::release_reaped(who, name_deposits);
+	}
+
+	/// Move a kitty of `who` to the shelter, returning its name deposit
+	fn shelter_kitty(who: &T::AccountId, kitty_id: T::KittyIndex) -> result::Result<BalanceOf<T, I>, &'static str> {
+		// Only fails if the kitty is already in the shelter, the lists are corrupted
+		<ShelterList<T, I>>::append(&(), kitty_id)?;
+		let _ = <OwnedKittiesList<T, I>>::remove(who, kitty_id);
+		<KittyOwners<T, I>>::remove(kitty_id);
+		<StrandedKitties<T, I>>::remove(kitty_id);
+		Self::delist_kitty(kitty_id);
+		<PendingTransfers<T, I>>::remove(kitty_id);
+		<BreedingDelegations<T, I>>::remove(kitty_id);
+
+		Self::deposit_event(RawEvent::Sheltered(who.clone(), kitty_id));
+		Ok(Self::take_name(kitty_id))
+	}
+
+	/// Shelter a kitty left with a reaped owner once it is unlocked, unless the owner was funded again
+	fn shelter_stranded(kitty_id: T::KittyIndex) {
+		if !<StrandedKitties<T, I>>::take(kitty_id) {
+			return;
+		}
+		let owner = match Self::kitty_owner(kitty_id) {
+			Some(owner) => owner,
+			None => return,
+		};
+		if !T::Currency::free_balance(&owner).is_zero() {
+			return;
+		}
+
+		match Self::shelter_kitty(&owner, kitty_id) {
+			Ok(name_deposit) => Self::release_reaped(&owner, name_deposit),
+			Err(_) => Self::deposit_event(RawEvent::NotSheltered(owner, kitty_id)),
+		}
+	}
+
+	/// Drop the reap lock of `who` once it has no kitties left and refund the name deposits of its sheltered kitties
+	fn release_reaped(who: &T::AccountId, name_deposits: BalanceOf<T, I>) {
+		if <OwnedKittiesList<T, I>>::clear(who).is_ok() {
+			<TransferApprovalRequired<T, I>>::remove(who);
+			T::Currency::remove_lock(T::ReapLockId::get(), who);
+		}
+		// Refund after the kitties are sheltered, as the refund can reap the account and call `shelter_kitties` again
+		T::Currency::unreserve(who, name_deposits);
+	}
+
+	fn insert_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex, kitty: Kitty) -> result::Result<(), &'static str> {
+		Self::ensure_can_receive(owner)?;
+		Self::store_kitty(owner, kitty_id, kitty)
+	}
+
+	/// Store a new kitty of `owner` without checking the account limit
+	fn store_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex, kitty: Kitty) -> result::Result<(), &'static str> {
+		Self::insert_owned_kitty(owner, kitty_id)?;
+
+		Self::add_kitty(kitty_id, kitty);
+		<KittyOwners<T, I>>::insert(kitty_id, owner.clone());
+
+		T::OnKittyCreated::on_kitty_created(Some(owner), kitty_id);
+
+		Ok(())
+	}
+
+	/// Create and store a kitty, born now. The caller places it with an owner or in the shelter.
+	fn add_kitty(kitty_id: T::KittyIndex, kitty: Kitty) {
+		<Kitties<T, I>>::insert(kitty_id, kitty);
+		<KittiesCount<T, I>>::put(kitty_id + 1.into());
+		<KittyBirths<T, I>>::insert(kitty_id, <system::Module<T>>::block_number());
+	}
+
+	/// The owner of a kitty and the delegation that lets `who` breed it, if `who` can breed it.
+	/// The delegation is `None` when `who` is the owner.
+	fn breeding_right(who: &T::AccountId, kitty_id: T::KittyIndex) -> Option<(T::AccountId, Option<BreedingDelegationOf<T>>)> {
+		let owner = Self::kitty_owner(kitty_id)?;
+		if owner == *who {
+			return Some((owner, None));
+		}
+
+		let now = <system::Module<T>>::block_number();
+		Self::breeding_delegation(kitty_id)
+			.filter(|delegation| delegation.delegate == *who && delegation.expires_at >= now && delegation.uses_left > 0)
+			.map(|delegation| (owner, Some(delegation)))
+	}
 
-		Self::insert_owned_kitty(owner, kitty_id);
+	/// Use up one breeding of a delegation, removing it when none is left
+	fn use_breeding_delegation(kitty_id: T::KittyIndex, mut delegation: BreedingDelegationOf<T>) {
+		delegation.uses_left -= 1;
+		if delegation.uses_left == 0 {
+			<BreedingDelegations<T, I>>::remove(kitty_id);
+		} else {
+			<BreedingDelegations<T, I>>::insert(kitty_id, delegation);
+		}
 	}
 
-	fn do_breed(sender: &T::AccountId, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> result::Result<T::KittyIndex, &'static str> {
+	/// Breed two kitties of the same owner, either as the owner or as their breeding delegate.
+	/// `sender` pays the fee. Returns the owner of the egg, the egg and its hatching block.
+	fn do_breed(
+		sender: &T::AccountId,
+		kitty_id_1: T::KittyIndex,
+		kitty_id_2: T::KittyIndex,
+	) -> result::Result<(T::AccountId, EggId, T::BlockNumber), &'static str> {
 		let kitty1 = Self::kitty(kitty_id_1);
 		let kitty2 = Self::kitty(kitty_id_2);
 
 		ensure!(kitty1.is_some(), "Invalid kitty_id_1");
 		ensure!(kitty2.is_some(), "Invalid kitty_id_2");
 		ensure!(kitty_id_1 != kitty_id_2, "Needs different parent");
-		ensure!(Self::kitty_owner(&kitty_id_1).map(|owner| owner == *sender).unwrap_or(false), "Not onwer of kitty1");
- 		ensure!(Self::kitty_owner(&kitty_id_2).map(|owner| owner == *sender).unwrap_or(false), "Not owner of kitty2");
-
-		let kitty_id = Self::next_kitty_id()?;
+		ensure!(!Self::is_frozen(kitty_id_1) && !Self::is_frozen(kitty_id_2), "Kitty is frozen");
+		ensure!(!Self::is_locked(kitty_id_1) && !Self::is_locked(kitty_id_2), "Kitty is locked");
+		let (owner, delegation_1) = Self::breeding_right(sender, kitty_id_1).ok_or("Not onwer of kitty1")?;
+		let (owner_2, delegation_2) = Self::breeding_right(sender, kitty_id_2).ok_or("Not owner of kitty2")?;
+		ensure!(owner == owner_2, "Parents have different owners");
+		ensure!(
+			Self::life_stage(kitty_id_1) == Some(LifeStage::Adult) && Self::life_stage(kitty_id_2) == Some(LifeStage::Adult),
+			"Only adults can breed"
+		);
+
+		// The kitty gets its id when it hatches, but fail early if there is none left
+		Self::next_kitty_id()?;
+		let egg_id = Self::next_egg_id();
+		let next_egg_id = egg_id.checked_add(1).ok_or("Eggs count overflow")?;
+		// The offspring goes to the delegate only if both delegations say so
+		let egg_owner = match (&delegation_1, &delegation_2) {
+			(Some(d1), Some(d2)) if d1.offspring_to_delegate && d2.offspring_to_delegate => sender.clone(),
+			_ => owner,
+		};
+		// Check before charging the fee
+		Self::ensure_can_receive(&egg_owner)?;
+
+		let generation = Self::kitty_generation(kitty_id_1).max(Self::kitty_generation(kitty_id_2));
+		let fee = Self::breeding_fee_for(generation);
+		let imbalance = T::Currency::withdraw(sender, fee, WithdrawReason::Fee, ExistenceRequirement::KeepAlive)?;
+		T::BreedingFeeDestination::on_unbalanced(imbalance);
 
 		let kitty1_dna = kitty1.unwrap().0;
 		let kitty2_dna = kitty2.unwrap().0;
@@ -190,17 +1214,535 @@ impl<T: Trait> Module<T> {
 			new_dna[i] = combine_dna(kitty1_dna[i], kitty2_dna[i], selector[i]);
 		}
 
-		Self::insert_kitty(sender, kitty_id, Kitty(new_dna));
+		Self::lock(kitty_id_1)?;
+		Self::lock(kitty_id_2)?;
+		if let Some(delegation) = delegation_1 {
+			Self::use_breeding_delegation(kitty_id_1, delegation);
+		}
+		if let Some(delegation) = delegation_2 {
+			Self::use_breeding_delegation(kitty_id_2, delegation);
+		}
 
-		Ok(kitty_id)
-	}
+		let hatch_at = <system::Module<T>>::block_number().saturating_add(T::HatchingPeriod::get().max(One::one()));
+		<Eggs<T, I>>::insert(egg_id, Egg {
+			owner: egg_owner.clone(),
+			parents: (kitty_id_1, kitty_id_2),
+			dna: new_dna,
+			generation: generation.saturating_add(1),
+			hatch_at,
+		});
+		<NextEggId<I>>::put(next_egg_id);
+		<OwnedEggs<T, I>>::mutate(&egg_owner, |eggs| eggs.push(egg_id));
+		<EggsHatchingAt<T, I>>::mutate(hatch_at, |eggs| eggs.push(egg_id));
 
-	fn do_transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex)  {
- 		<OwnedKittiesList<T>>::remove(&from, kitty_id);
- 		<OwnedKittiesList<T>>::append(&to, kitty_id);
- 		<KittyOwners<T>>::insert(kitty_id, to);
- 	}
-}
+		Ok((egg_owner, egg_id, hatch_at))
+	}
+
+	/// Record the lengths of the lists of kitty owners, the shelter and the listings written before lengths
+	/// were recorded. Walks at most `MAX_LENGTH_RECORD_STEPS` kitties and list values, so it may take several blocks.
+	fn record_lengths() {
+		let count = Self::kitties_count();
+		let mut kitty_id = <NextLengthRecord<T, I>>::get();
+		let mut steps = 0u32;
+		while kitty_id < count && steps < MAX_LENGTH_RECORD_STEPS {
+			let owners = match Self::co_owners(kitty_id) {
+				Some(co_ownership) => co_ownership.owners.into_iter().map(|(who, _)| who).collect(),
+				None => Self::kitty_owner(kitty_id).into_iter().collect::<Vec<_>>(),
+			};
+			for owner in owners.iter() {
+				steps = steps.saturating_add(<OwnedKittiesList<T, I>>::record_len(owner));
+			}
+			steps = steps.saturating_add(1);
+			kitty_id = kitty_id.saturating_add(One::one());
+		}
+		if kitty_id < count {
+			<NextLengthRecord<T, I>>::put(kitty_id);
+			return;
+		}
+
+		<ShelterList<T, I>>::record_len(&());
+		<ListingsList<T, I>>::record_len(&());
+		<NextLengthRecord<T, I>>::kill();
+		<LengthsRecorded<T, I>>::put(true);
+	}
+
+	/// Hatch the eggs due in block `now`. An egg that cannot hatch is tried again in the next block,
+	/// in at most `MAX_HATCH_ATTEMPTS` blocks. Then it is discarded and its parents are unlocked.
+	fn hatch_eggs(now: T::BlockNumber) {
+		for egg_id in <EggsHatchingAt<T, I>>::take(now) {
+			// Eggs of reaped accounts already hatched into the shelter
+			let egg = match Self::egg(egg_id) {
+				Some(egg) => egg,
+				None => continue,
+			};
+			if Self::hatch(egg_id, &egg).is_ok() {
+				continue;
+			}
+
+			if now.saturating_sub(egg.hatch_at) < (MAX_HATCH_ATTEMPTS - 1).saturated_into::<T::BlockNumber>() {
+				<EggsHatchingAt<T, I>>::mutate(now.saturating_add(One::one()), |eggs| eggs.push(egg_id));
+			} else {
+				Self::remove_egg(egg_id, &egg);
+				Self::deposit_event(RawEvent::EggDiscarded(egg.owner, egg_id));
+			}
+		}
+	}
+
+	fn hatch(egg_id: EggId, egg: &EggOf<T, I>) -> result::Result<(), &'static str> {
+		let kitty_id = Self::next_kitty_id()?;
+
+		// The egg already counted towards the account limit
+		Self::store_kitty(&egg.owner, kitty_id, Kitty(egg.dna))?;
+		<KittyGenerations<T, I>>::insert(kitty_id, egg.generation);
+		Self::remove_egg(egg_id, egg);
+
+		Self::deposit_event(RawEvent::Created(egg.owner.clone(), kitty_id));
+		Self::deposit_event(RawEvent::Hatched(egg.owner.clone(), egg_id, kitty_id));
+		Ok(())
+	}
+
+	/// Hatch an egg of a reaped account into the shelter, without waiting for its hatching block
+	fn hatch_into_shelter(egg_id: EggId, egg: &EggOf<T, I>) -> result::Result<(), &'static str> {
+		let kitty_id = Self::next_kitty_id()?;
+
+		<ShelterList<T, I>>::append(&(), kitty_id)?;
+		Self::add_kitty(kitty_id, Kitty(egg.dna));
+		<KittyGenerations<T, I>>::insert(kitty_id, egg.generation);
+		Self::remove_egg(egg_id, egg);
+
+		T::OnKittyCreated::on_kitty_created(None, kitty_id);
+
+		Self::deposit_event(RawEvent::Hatched(egg.owner.clone(), egg_id, kitty_id));
+		Self::deposit_event(RawEvent::Sheltered(egg.owner.clone(), kitty_id));
+		Ok(())
+	}
+
+	/// Remove a hatched or discarded egg and unlock its parents
+	fn remove_egg(egg_id: EggId, egg: &EggOf<T, I>) {
+		<Eggs<T, I>>::remove(egg_id);
+		<OwnedEggs<T, I>>::mutate(&egg.owner, |eggs| eggs.retain(|id| *id != egg_id));
+		// The parents were locked when the egg was laid
+		let _ = Self::unlock(egg.parents.0);
+		let _ = Self::unlock(egg.parents.1);
+		Self::shelter_stranded(egg.parents.0);
+		Self::shelter_stranded(egg.parents.1);
+	}
+
+	/// Move a kitty between single owners. `price` is the price paid if it is sold.
+	fn do_transfer(
+		from: &T::AccountId,
+		to: &T::AccountId,
+		kitty_id: T::KittyIndex,
+		price: Option<BalanceOf<T, I>>,
+	) -> result::Result<(), &'static str> {
+		// Check before writing, so a failure leaves both lists untouched
+		ensure!(!Self::is_co_owned(kitty_id), "Kitty is co-owned");
+		ensure!(!<OwnedKittiesList<T, I>>::contains(to, kitty_id), "Kitty is already owned by recipient");
+		ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+		Self::ensure_can_receive(to)?;
+
+ 		Self::remove_owned_kitty(&from, kitty_id)?;
+ 		Self::insert_owned_kitty(&to, kitty_id)?;
+ 		<KittyOwners<T, I>>::insert(kitty_id, to);
+		<StrandedKitties<T, I>>::remove(kitty_id);
+		// The price and the breeding delegation were set by the previous owner
+		Self::delist_kitty(kitty_id);
+		<BreedingDelegations<T, I>>::remove(kitty_id);
+		Self::transfer_name(from, to, kitty_id);
+
+		T::OnKittyTransferred::on_kitty_transferred(Some(from), to, kitty_id, price);
+
+		Ok(())
+ 	}
+
+	fn is_co_owned(kitty_id: T::KittyIndex) -> bool {
+		<CoOwners<T, I>>::exists(kitty_id)
+	}
+
+	fn is_co_owner(co_ownership: &CoOwnership<T::AccountId>, who: &T::AccountId) -> bool {
+		co_ownership.owners.iter().any(|(owner, _)| owner == who)
+	}
+
+	/// Execute a proposal that has enough approvals, or keep it open. Returns whether it was executed.
+	fn settle_proposal(
+		kitty_id: T::KittyIndex,
+		co_ownership: &CoOwnership<T::AccountId>,
+		proposal: CoOwnerProposalOf<T, I>,
+	) -> result::Result<bool, &'static str> {
+		if (proposal.approvals.len() as u32) < co_ownership.threshold {
+			<CoOwnerProposals<T, I>>::insert(kitty_id, proposal);
+			return Ok(false);
+		}
+
+		Self::execute_co_owned(kitty_id, co_ownership, proposal.action)?;
+		<CoOwnerProposals<T, I>>::remove(kitty_id);
+		Ok(true)
+	}
+
+	fn execute_co_owned(
+		kitty_id: T::KittyIndex,
+		co_ownership: &CoOwnership<T::AccountId>,
+		action: CoOwnedActionOf<T, I>,
+	) -> result::Result<(), &'static str> {
+		let owner = Self::kitty_owner(kitty_id).ok_or("Kitty does not exist")?;
+
+		match action {
+			CoOwnedAction::Transfer(to) => {
+				ensure!(!Self::is_frozen(kitty_id), "Kitty is frozen");
+				ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+				if !Self::is_co_owner(co_ownership, &to) {
+					Self::ensure_can_receive(&to)?;
+				}
+
+				Self::give_co_owned(kitty_id, co_ownership, &to, None)?;
+
+				Self::deposit_event(RawEvent::Transferred(owner, to, kitty_id));
+			}
+			CoOwnedAction::Ask(price, hint) => {
+				Self::set_price(kitty_id, price, hint)?;
+
+				Self::deposit_event(RawEvent::Ask(owner, kitty_id, price));
+			}
+			CoOwnedAction::Breed(partner) => {
+				ensure!(Self::co_owners(partner).as_ref() == Some(co_ownership), "Partner has other co-owners");
+
+				let (egg_owner, egg_id, hatch_at) = Self::do_breed(&owner, kitty_id, partner)?;
+
+				Self::deposit_event(RawEvent::EggLaid(egg_owner, egg_id, hatch_at));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Split `price` paid by `buyer` between the co-owners by share. The first co-owner gets what rounding leaves.
+	fn pay_co_owners(
+		buyer: &T::AccountId,
+		co_ownership: &CoOwnership<T::AccountId>,
+		price: BalanceOf<T, I>,
+	) -> result::Result<(), &'static str> {
+		let total_shares = co_ownership.owners.iter()
+			.fold(0u64, |total, (_, share)| total.saturating_add(*share as u64))
+			.saturated_into::<BalanceOf<T, I>>();
+
+		// Withdraw once, so the buyer pays either everyone or no one
+		let mut payment = T::Currency::withdraw(buyer, price, WithdrawReason::Transfer, ExistenceRequirement::KeepAlive)?;
+		for (who, share) in co_ownership.owners.iter().skip(1) {
+			let (part, rest) = payment.split(price.saturating_mul((*share).into()) / total_shares);
+			payment = rest;
+			T::Currency::resolve_creating(who, part);
+		}
+		T::Currency::resolve_creating(&co_ownership.owners[0].0, payment);
+
+		Ok(())
+	}
+
+	/// End the co-ownership of a kitty, leaving it to `to` alone. `price` is the price paid if it is sold.
+	fn give_co_owned(
+		kitty_id: T::KittyIndex,
+		co_ownership: &CoOwnership<T::AccountId>,
+		to: &T::AccountId,
+		price: Option<BalanceOf<T, I>>,
+	) -> result::Result<(), &'static str> {
+		let owner = Self::kitty_owner(kitty_id).ok_or("Kitty does not exist")?;
+
+		for (who, _) in co_ownership.owners.iter() {
+			if who != to {
+				Self::remove_owned_kitty(who, kitty_id)?;
+			}
+		}
+		if !Self::is_co_owner(co_ownership, to) {
+			Self::insert_owned_kitty(to, kitty_id)?;
+		}
+		<KittyOwners<T, I>>::insert(kitty_id, to);
+		<CoOwners<T, I>>::remove(kitty_id);
+		<CoOwnerProposals<T, I>>::remove(kitty_id);
+		Self::delist_kitty(kitty_id);
+		Self::transfer_name(&owner, to, kitty_id);
+
+		T::OnKittyTransferred::on_kitty_transferred(Some(&owner), to, kitty_id, price);
+
+		Ok(())
+	}
+
+	/// Drop a reaped account from the co-owners of a kitty, returning the name deposit reserved from it.
+	/// The last co-owner left becomes the single owner.
+	fn leave_co_ownership(
+		who: &T::AccountId,
+		kitty_id: T::KittyIndex,
+		mut co_ownership: CoOwnership<T::AccountId>,
+	) -> BalanceOf<T, I> {
+		co_ownership.owners.retain(|(owner, _)| owner != who);
+		// The approvals may include the account
+		<CoOwnerProposals<T, I>>::remove(kitty_id);
+
+		let mut name_deposit = Zero::zero();
+		if Self::kitty_owner(kitty_id).as_ref() == Some(who) {
+			if let Some((next, _)) = co_ownership.owners.first() {
+				<KittyOwners<T, I>>::insert(kitty_id, next);
+			}
+			name_deposit = Self::take_name(kitty_id);
+		}
+
+		if co_ownership.owners.len() < 2 {
+			<CoOwners<T, I>>::remove(kitty_id);
+		} else {
+			co_ownership.threshold = co_ownership.threshold.min(co_ownership.owners.len() as u32);
+			<CoOwners<T, I>>::insert(kitty_id, co_ownership);
+		}
+
+		name_deposit
+	}
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// The most kitties an account can own
+	pub fn max_kitties_per_account() -> u32 {
+		Self::kitties_per_account_limit().unwrap_or_else(T::MaxKittiesPerAccount::get)
+	}
+
+	/// Number of kitties owned by `owner`
+	pub fn kitty_count_of(owner: &T::AccountId) -> u32 {
+		<OwnedKittiesList<T, I>>::len(owner)
+	}
+
+	/// Kitties of `owner` in list order, following `start_after` or from the start if it is `None`.
+	/// Returns at most `limit` kitties, capped at `MAX_KITTIES_PAGE`.
+	pub fn kitties_of(owner: &T::AccountId, start_after: Option<T::KittyIndex>, limit: u32) -> Vec<T::KittyIndex> {
+		<OwnedKittiesList<T, I>>::page(owner, start_after, limit.min(MAX_KITTIES_PAGE))
+	}
+
+	/// The fee to breed two kitties now. See `breeding_fee_for`.
+	pub fn breeding_fee(kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> BalanceOf<T, I> {
+		Self::breeding_fee_for(Self::kitty_generation(kitty_id_1).max(Self::kitty_generation(kitty_id_2)))
+	}
+
+	/// The fee to breed a kitty whose older parent is of `generation`, at the current supply:
+	/// `BreedingBaseFee + BreedingFeePerKitty * KittiesCount + BreedingFeePerGeneration * generation`, with the fees set by root if any
+	pub fn breeding_fee_for(generation: u32) -> BalanceOf<T, I> {
+		let supply = Self::kitties_count().saturated_into::<u64>().saturated_into::<BalanceOf<T, I>>();
+		let (base, per_kitty, per_generation) = Self::breeding_fees().unwrap_or_else(|| (
+			T::BreedingBaseFee::get(),
+			T::BreedingFeePerKitty::get(),
+			T::BreedingFeePerGeneration::get(),
+		));
+		base
+			.saturating_add(per_kitty.saturating_mul(supply))
+			.saturating_add(per_generation.saturating_mul(generation.into()))
+	}
+
+	/// The age of a kitty in blocks. Kitties born before ageing was tracked count as adults when it started,
+	/// so the existing kitties can still breed after the upgrade.
+	pub fn kitty_age(kitty_id: T::KittyIndex) -> T::BlockNumber {
+		let now = <system::Module<T>>::block_number();
+		match <KittyBirths<T, I>>::get(kitty_id) {
+			Some(birth) => now.saturating_sub(birth),
+			None => now.saturating_sub(Self::ageing_since().unwrap_or(now)).saturating_add(T::KittenPeriod::get()),
+		}
+	}
+
+	/// The stage of life of a kitty in the current block. `None` if it does not exist.
+	pub fn life_stage(kitty_id: T::KittyIndex) -> Option<LifeStage> {
+		if !<Kitties<T, I>>::exists(kitty_id) {
+			return None;
+		}
+		Some(Self::life_stage_at(Self::kitty_age(kitty_id)))
+	}
+
+	/// The stage of life of a kitty of `age` blocks
+	pub fn life_stage_at(age: T::BlockNumber) -> LifeStage {
+		let kitten_period = T::KittenPeriod::get();
+		if age < kitten_period {
+			return LifeStage::Kitten;
+		}
+		if age < kitten_period.saturating_add(T::AdultPeriod::get()) {
+			return LifeStage::Adult;
+		}
+		match T::Lifespan::get() {
+			Some(lifespan) if age >= lifespan => LifeStage::Legendary,
+			_ => LifeStage::Elder,
+		}
+	}
+
+	/// How a kitty looks in the current block. `None` if it does not exist.
+	pub fn appearance(kitty_id: T::KittyIndex) -> Option<Appearance> {
+		let kitty = Self::kitty(kitty_id)?;
+		let stage = Self::life_stage(kitty_id)?;
+		Some(Appearance::from_dna(&kitty.0, stage))
+	}
+
+	/// The cheapest kitty for sale and its price
+	pub fn cheapest_kitty() -> Option<(T::KittyIndex, BalanceOf<T, I>)> {
+		<ListingsList<T, I>>::first(&()).and_then(|kitty_id| Self::kitty_price(kitty_id).map(|price| (kitty_id, price)))
+	}
+
+	/// Kitties for sale in price order, following `start_after` or from the cheapest if it is `None`.
+	/// Returns at most `limit` kitties, capped at `MAX_KITTIES_PAGE`.
+	pub fn listings_page(start_after: Option<T::KittyIndex>, limit: u32) -> Vec<(T::KittyIndex, BalanceOf<T, I>)> {
+		<ListingsList<T, I>>::page(&(), start_after, limit.min(MAX_KITTIES_PAGE))
+			.into_iter()
+			.filter_map(|kitty_id| Self::kitty_price(kitty_id).map(|price| (kitty_id, price)))
+			.collect()
+	}
+
+	/// Verify that `Kitties`, `KittiesCount`, `KittyOwners`, `OwnedKitties`, `KittyPrices`,
+	/// the listings and the shelter agree with each other, returning every discrepancy found.
+	///
+	/// This walks the whole kitties storage, so it is only meant for tests and debugging.
+	/// Owner lists are found through `KittyOwners` and `CoOwners`; a list of an account that owns no kitty is not visited.
+	pub fn check_integrity() -> Vec<IntegrityError<T::AccountId, T::KittyIndex>> {
+		let count = Self::kitties_count();
+		let limit = count.saturated_into::<u64>() as usize;
+		let mut errors = Vec::new();
+		let mut owners = BTreeSet::new();
+
+		let mut kitty_id = T::KittyIndex::zero();
+		while kitty_id < count {
+			if !<Kitties<T, I>>::exists(kitty_id) {
+				errors.push(IntegrityError::MissingKitty(kitty_id));
+			}
+
+			let sheltered = <Shelter<T, I>>::exists(&((), Some(kitty_id)));
+			match Self::kitty_owner(kitty_id) {
+				Some(owner) => {
+					if sheltered {
+						errors.push(IntegrityError::OwnedAndSheltered(kitty_id));
+					}
+					if !<OwnedKitties<T, I>>::exists(&(owner.clone(), Some(kitty_id))) {
+						errors.push(IntegrityError::NotInOwnerList(owner.clone(), kitty_id));
+					}
+					if <KittyPrices<T, I>>::exists(kitty_id) && !<ListingsList<T, I>>::contains(&(), kitty_id) {
+						errors.push(IntegrityError::NotListed(kitty_id));
+					}
+					for (co_owner, _) in Self::co_owners(kitty_id).map(|c| c.owners).unwrap_or_default() {
+						if !<OwnedKitties<T, I>>::exists(&(co_owner.clone(), Some(kitty_id))) {
+							errors.push(IntegrityError::NotInOwnerList(co_owner.clone(), kitty_id));
+						}
+						owners.insert(co_owner);
+					}
+					owners.insert(owner);
+				}
+				None => {
+					if !sheltered {
+						errors.push(IntegrityError::Unowned(kitty_id));
+					}
+					if <KittyPrices<T, I>>::exists(kitty_id) {
+						errors.push(IntegrityError::PriceWithoutOwner(kitty_id));
+					}
+				}
+			}
+
+			kitty_id = kitty_id + One::one();
+		}
+
+		if <Kitties<T, I>>::exists(count) {
+			errors.push(IntegrityError::KittyBeyondCount(count));
+		}
+
+		for owner in owners {
+			let (kitties, link_errors) = <OwnedKittiesList<T, I>>::check(&owner, limit);
+			errors.extend(link_errors.into_iter().map(|e| IntegrityError::BrokenOwnerList(owner.clone(), e)));
+			for kitty_id in kitties {
+				let co_owned = Self::co_owners(kitty_id).map_or(false, |c| Self::is_co_owner(&c, &owner));
+				if Self::kitty_owner(kitty_id).as_ref() != Some(&owner) && !co_owned {
+					errors.push(IntegrityError::OwnerMismatch(owner.clone(), kitty_id));
+				}
+			}
+		}
+
+		let (_, link_errors) = <ShelterList<T, I>>::check(&(), limit);
+		errors.extend(link_errors.into_iter().map(IntegrityError::BrokenShelter));
+
+		let (listed, link_errors) = <ListingsList<T, I>>::check(&(), limit);
+		errors.extend(link_errors.into_iter().map(IntegrityError::BrokenListings));
+		for kitty_id in listed {
+			if !<KittyPrices<T, I>>::exists(kitty_id) {
+				errors.push(IntegrityError::ListedWithoutPrice(kitty_id));
+			}
+		}
+
+		errors
+	}
+}
+
+impl<T: Trait<I>, I: Instance> NonFungibleAsset<T::AccountId> for Module<T, I> {
+	type AssetId = T::KittyIndex;
+
+	fn exists(kitty_id: T::KittyIndex) -> bool {
+		<Kitties<T, I>>::exists(kitty_id)
+	}
+
+	fn owner_of(kitty_id: T::KittyIndex) -> Option<T::AccountId> {
+		// A co-owned kitty has no single owner to act for it
+		Self::kitty_owner(kitty_id).filter(|_| !Self::is_co_owned(kitty_id))
+	}
+
+	fn total_supply() -> u64 {
+		Self::kitties_count().saturated_into::<u64>()
+	}
+
+	fn transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex) -> result::Result<(), &'static str> {
+		ensure!(Self::kitty_owner(kitty_id).as_ref() == Some(from), "Only owner can transfer kitty");
+		ensure!(from != to, "Cannot transfer kitty to yourself");
+		ensure!(!Self::is_frozen(kitty_id), "Kitty is frozen");
+
+		Self::do_transfer(from, to, kitty_id, None)?;
+
+		<PendingTransfers<T, I>>::remove(kitty_id);
+
+		Self::deposit_event(RawEvent::Transferred(from.clone(), to.clone(), kitty_id));
+		Ok(())
+	}
+
+	fn lock(kitty_id: T::KittyIndex) -> result::Result<(), &'static str> {
+		ensure!(<Kitties<T, I>>::exists(kitty_id), "Kitty does not exist");
+		ensure!(!Self::is_locked(kitty_id), "Kitty is locked");
+
+		<LockedKitties<T, I>>::insert(kitty_id, true);
+		Self::delist_kitty(kitty_id);
+
+		Self::deposit_event(RawEvent::Locked(kitty_id));
+		Ok(())
+	}
+
+	fn unlock(kitty_id: T::KittyIndex) -> result::Result<(), &'static str> {
+		ensure!(Self::is_locked(kitty_id), "Kitty is not locked");
+
+		<LockedKitties<T, I>>::remove(kitty_id);
+
+		Self::deposit_event(RawEvent::Unlocked(kitty_id));
+		Ok(())
+	}
+
+	fn is_locked(kitty_id: T::KittyIndex) -> bool {
+		<LockedKitties<T, I>>::get(kitty_id)
+	}
+
+	fn can_receive(who: &T::AccountId) -> bool {
+		Self::ensure_can_receive(who).is_ok()
+	}
+}
+
+impl<T: Trait<I>, I: Instance> AssetDna<T::AccountId> for Module<T, I> {
+	fn dna(kitty_id: T::KittyIndex) -> Option<[u8; 16]> {
+		Self::kitty(kitty_id).map(|kitty| kitty.0)
+	}
+
+	/// Frozen and locked kitties sit out, so do kittens and legendary kitties
+	fn can_play(kitty_id: T::KittyIndex) -> bool {
+		let fit = match Self::life_stage(kitty_id) {
+			Some(LifeStage::Adult) | Some(LifeStage::Elder) => true,
+			_ => false,
+		};
+		fit && !Self::is_frozen(kitty_id) && !Self::is_locked(kitty_id)
+	}
+}
+
+impl<T: Trait<I>, I: Instance> OnFreeBalanceZero<T::AccountId> for Module<T, I> {
+	fn on_free_balance_zero(who: &T::AccountId) {
+		Self::shelter_kitties(who);
+	}
+}
 
 /// Tests for Kitties module
 #[cfg(test)]
@@ -209,15 +1751,30 @@ mod tests {
 
 	use runtime_io::with_externalities;
 	use primitives::{H256, Blake2Hasher};
-	use support::{impl_outer_origin, parameter_types};
-	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+	use support::{impl_outer_origin, impl_outer_event, assert_ok, assert_noop, parameter_types};
+	use sr_primitives::{traits::{BlakeTwo256, OnInitialize}, testing::Header};
+	use indices::address::Address;
 	use sr_primitives::weights::Weight;
 	use sr_primitives::Perbill;
+	use std::cell::RefCell;
 
 	impl_outer_origin! {
 		pub enum Origin for Test {}
 	}
 
+	mod kitties {
+		pub use crate::kitties::{Event, Instance1};
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for Test {
+			balances<T>,
+			indices<T>,
+			kitties<T>,
+			kitties Instance1<T>,
+		}
+	}
+
 	// For testing the module, we construct most of a mock runtime. This means
 	// first constructing a configuration type (`Test`) which `impl`s each of the
 	// configuration traits of modules we want to use.
@@ -237,10 +1794,10 @@ mod tests {
 		type Hash = H256;
 		type Hashing = BlakeTwo256;
 		type AccountId = u64;
-		type Lookup = IdentityLookup<Self::AccountId>;
+		type Lookup = Indices;
 		type Header = Header;
 		type WeightMultiplierUpdate = ();
-		type Event = ();
+		type Event = TestEvent;
 		type BlockHashCount = BlockHashCount;
 		type MaximumBlockWeight = MaximumBlockWeight;
 		type MaximumBlockLength = MaximumBlockLength;
@@ -248,7 +1805,6 @@ mod tests {
 		type Version = ();
 	}
 	parameter_types! {
-		pub const ExistentialDeposit: u64 = 0;
 		pub const TransferFee: u64 = 0;
 		pub const CreationFee: u64 = 0;
 		pub const TransactionBaseFee: u64 = 0;
@@ -256,9 +1812,9 @@ mod tests {
 	}
 	impl balances::Trait for Test {
 		type Balance = u64;
-		type OnFreeBalanceZero = ();
-		type OnNewAccount = ();
-		type Event = ();
+		type OnFreeBalanceZero = (KittyModule, PuppyModule);
+		type OnNewAccount = Indices;
+		type Event = TestEvent;
 		type TransactionPayment = ();
 		type TransferPayment = ();
 		type DustRemoval = ();
@@ -269,23 +1825,237 @@ mod tests {
 		type TransactionByteFee = TransactionByteFee;
 		type WeightToFee = ();
 	}
+	impl indices::Trait for Test {
+		type AccountIndex = u32;
+		type ResolveHint = indices::SimpleResolveHint<Self::AccountId, Self::AccountIndex>;
+		type IsDeadAccount = Balances;
+		type Event = TestEvent;
+	}
+	parameter_types! {
+		pub const KittyReapPolicy: ReapPolicy = ReapPolicy::Shelter;
+		pub const PuppyReapPolicy: ReapPolicy = ReapPolicy::BlockReaping;
+		pub const KittiesLockId: LockIdentifier = *b"kitties ";
+		pub const PuppiesLockId: LockIdentifier = *b"puppies ";
+		pub const NameByteDeposit: u64 = 10;
+		pub const MaxNameLength: u32 = 8;
+		pub const BreedingBaseFee: u64 = 5;
+		pub const BreedingFeePerKitty: u64 = 1;
+		pub const BreedingFeePerGeneration: u64 = 10;
+		pub const HatchingPeriod: u64 = 2;
+	}
+	/// Collects the breeding fees in the account `FEE_COLLECTOR`
+	pub struct FeeCollector;
+	impl OnUnbalanced<balances::NegativeImbalance<Test>> for FeeCollector {
+		fn on_unbalanced(amount: balances::NegativeImbalance<Test>) {
+			Balances::resolve_creating(&FEE_COLLECTOR, amount);
+		}
+	}
+	const FEE_COLLECTOR: u64 = 99;
+	thread_local! {
+		static EXISTENTIAL_DEPOSIT: RefCell<u64> = RefCell::new(0);
+		static MAX_KITTIES_PER_ACCOUNT: RefCell<u32> = RefCell::new(u32::max_value());
+		static MAX_GEN0_KITTIES: RefCell<u32> = RefCell::new(u32::max_value());
+		static KITTEN_PERIOD: RefCell<u64> = RefCell::new(0);
+		static ADULT_PERIOD: RefCell<u64> = RefCell::new(u64::max_value());
+		static LIFESPAN: RefCell<Option<u64>> = RefCell::new(None);
+		static CREATED: RefCell<Vec<(Option<u64>, u32)>> = RefCell::new(vec![]);
+		static TRANSFERRED: RefCell<Vec<(Option<u64>, u64, u32, Option<u64>)>> = RefCell::new(vec![]);
+	}
+	/// Records the calls of the kitty hooks in `CREATED` and `TRANSFERRED`
+	pub struct RecordHooks;
+	impl OnKittyCreated<u64, u32> for RecordHooks {
+		fn on_kitty_created(owner: Option<&u64>, kitty_id: u32) {
+			CREATED.with(|v| v.borrow_mut().push((owner.cloned(), kitty_id)));
+		}
+	}
+	impl OnKittyTransferred<u64, u32, u64> for RecordHooks {
+		fn on_kitty_transferred(from: Option<&u64>, to: &u64, kitty_id: u32, price: Option<u64>) {
+			TRANSFERRED.with(|v| v.borrow_mut().push((from.cloned(), *to, kitty_id, price)));
+		}
+	}
+	pub struct ExistentialDeposit;
+	impl Get<u64> for ExistentialDeposit {
+		fn get() -> u64 { EXISTENTIAL_DEPOSIT.with(|v| *v.borrow()) }
+	}
+	pub struct MaxKittiesPerAccount;
+	impl Get<u32> for MaxKittiesPerAccount {
+		fn get() -> u32 { MAX_KITTIES_PER_ACCOUNT.with(|v| *v.borrow()) }
+	}
+	pub struct MaxGen0Kitties;
+	impl Get<u32> for MaxGen0Kitties {
+		fn get() -> u32 { MAX_GEN0_KITTIES.with(|v| *v.borrow()) }
+	}
+	pub struct KittenPeriod;
+	impl Get<u64> for KittenPeriod {
+		fn get() -> u64 { KITTEN_PERIOD.with(|v| *v.borrow()) }
+	}
+	pub struct AdultPeriod;
+	impl Get<u64> for AdultPeriod {
+		fn get() -> u64 { ADULT_PERIOD.with(|v| *v.borrow()) }
+	}
+	pub struct Lifespan;
+	impl Get<Option<u64>> for Lifespan {
+		fn get() -> Option<u64> { LIFESPAN.with(|v| *v.borrow()) }
+	}
 	impl Trait for Test {
 		type KittyIndex = u32;
-		type Currency = balances::Module<Test>;
-		type Event = ();
+		type Currency = Balances;
+		type Event = TestEvent;
+		type KittyReapPolicy = KittyReapPolicy;
+		type ReapLockId = KittiesLockId;
+		type NameByteDeposit = NameByteDeposit;
+		type MaxNameLength = MaxNameLength;
+		type MaxKittiesPerAccount = MaxKittiesPerAccount;
+		type MaxGen0Kitties = MaxGen0Kitties;
+		type BreedingBaseFee = BreedingBaseFee;
+		type BreedingFeePerKitty = BreedingFeePerKitty;
+		type BreedingFeePerGeneration = BreedingFeePerGeneration;
+		type BreedingFeeDestination = FeeCollector;
+		type HatchingPeriod = HatchingPeriod;
+		type KittenPeriod = KittenPeriod;
+		type AdultPeriod = AdultPeriod;
+		type Lifespan = Lifespan;
+		type OnKittyCreated = (RecordHooks, ());
+		type OnKittyTransferred = (RecordHooks, ());
+	}
+	// A second collection with its own storage, index type and reap policy
+	impl Trait<Instance1> for Test {
+		type KittyIndex = u64;
+		type Currency = Balances;
+		type Event = TestEvent;
+		type KittyReapPolicy = PuppyReapPolicy;
+		type ReapLockId = PuppiesLockId;
+		type NameByteDeposit = NameByteDeposit;
+		type MaxNameLength = MaxNameLength;
+		type MaxKittiesPerAccount = MaxKittiesPerAccount;
+		type MaxGen0Kitties = MaxGen0Kitties;
+		type BreedingBaseFee = BreedingBaseFee;
+		type BreedingFeePerKitty = BreedingFeePerKitty;
+		type BreedingFeePerGeneration = BreedingFeePerGeneration;
+		type BreedingFeeDestination = FeeCollector;
+		type HatchingPeriod = HatchingPeriod;
+		type KittenPeriod = KittenPeriod;
+		type AdultPeriod = AdultPeriod;
+		type Lifespan = Lifespan;
+		type OnKittyCreated = ();
+		type OnKittyTransferred = ();
 	}
 	type OwnedKittiesTest = OwnedKitties<Test>;
+	type KittyModule = Module<Test>;
+	type PuppyModule = Module<Test, Instance1>;
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+	type Indices = indices::Module<Test>;
+
+	/// Builds the genesis storage of the mock runtime
+	pub struct ExtBuilder {
+		balances: Vec<(u64, u64)>,
+		existential_deposit: u64,
+		kitties: Vec<u64>,
+		max_kitties_per_account: u32,
+		max_gen0_kitties: u32,
+		life_stages: (u64, u64, Option<u64>),
+	}
+
+	impl Default for ExtBuilder {
+		fn default() -> Self {
+			Self {
+				balances: vec![(1, 100), (2, 100), (3, 100)],
+				existential_deposit: 0,
+				kitties: vec![],
+				max_kitties_per_account: u32::max_value(),
+				max_gen0_kitties: u32::max_value(),
+				life_stages: (0, u64::max_value(), None),
+			}
+		}
+	}
+
+	impl ExtBuilder {
+		/// Endow accounts. Every endowed account also gets an index, in order.
+		pub fn balances(mut self, balances: Vec<(u64, u64)>) -> Self {
+			self.balances = balances;
+			self
+		}
+
+		/// Reap accounts whose balance drops below `deposit`
+		pub fn existential_deposit(mut self, deposit: u64) -> Self {
+			self.existential_deposit = deposit;
+			self
+		}
+
+		/// Pre-mint a kitty for `owner`. Kitties get ids in the order they are added.
+		pub fn kitty(mut self, owner: u64) -> Self {
+			self.kitties.push(owner);
+			self
+		}
+
+		/// Limit the kitties an account can own
+		pub fn max_kitties_per_account(mut self, max: u32) -> Self {
+			self.max_kitties_per_account = max;
+			self
+		}
+
+		/// Limit the kitties created with `create`. Pre-minted kitties do not count.
+		pub fn max_gen0_kitties(mut self, max: u32) -> Self {
+			self.max_gen0_kitties = max;
+			self
+		}
+
+		/// Age kitties. By default they are born adults and never grow old.
+		pub fn life_stages(mut self, kitten_period: u64, adult_period: u64, lifespan: Option<u64>) -> Self {
+			self.life_stages = (kitten_period, adult_period, lifespan);
+			self
+		}
+
+		pub fn build(self) -> runtime_io::TestExternalities<Blake2Hasher> {
+			EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
+			MAX_KITTIES_PER_ACCOUNT.with(|v| *v.borrow_mut() = self.max_kitties_per_account);
+			MAX_GEN0_KITTIES.with(|v| *v.borrow_mut() = self.max_gen0_kitties);
+			KITTEN_PERIOD.with(|v| *v.borrow_mut() = self.life_stages.0);
+			ADULT_PERIOD.with(|v| *v.borrow_mut() = self.life_stages.1);
+			LIFESPAN.with(|v| *v.borrow_mut() = self.life_stages.2);
+			let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+			indices::GenesisConfig::<Test> {
+				ids: self.balances.iter().map(|&(who, _)| who).collect(),
+			}.assimilate_storage(&mut t).unwrap();
+			balances::GenesisConfig::<Test> {
+				balances: self.balances,
+				vesting: vec![],
+			}.assimilate_storage(&mut t).unwrap();
+
+			let mut ext: runtime_io::TestExternalities<Blake2Hasher> = t.into();
+			let kitties = self.kitties;
+			with_externalities(&mut ext, || {
+				// Events are not recorded in the genesis block
+				System::set_block_number(1);
+
+				for owner in kitties {
+					let kitty_id = KittyModule::next_kitty_id().unwrap();
+					KittyModule::insert_kitty(&owner, kitty_id, Kitty([kitty_id as u8; 16])).unwrap();
+				}
+			});
+			ext
+		}
+	}
 
-	// This function basically just builds a genesis storage key/value store according to
-	// our desired mockup.
-	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
-		system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	fn last_event() -> TestEvent {
+		System::events().pop().expect("Event expected").event
+	}
+
+	/// Run the blocks up to `n`, hatching the eggs due
+	fn run_to_block(n: u64) {
+		while System::block_number() < n {
+			let next = System::block_number() + 1;
+			System::set_block_number(next);
+			KittyModule::on_initialize(next);
+			PuppyModule::on_initialize(next);
+		}
 	}
 
 	#[test]
 	fn owned_kitties_can_append_values() {
-		with_externalities(&mut new_test_ext(), || {
-			OwnedKittiesList::<Test>::append(&0, 1);
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(OwnedKittiesList::<Test>::append(&0, 1));
 
 			assert_eq!(OwnedKittiesTest::get(&(0, None)), Some(KittyLinkedItem::<Test> {
 				prev: Some(1),
@@ -297,7 +2067,7 @@ mod tests {
 				next: None,
 			}));
 
-			OwnedKittiesList::<Test>::append(&0, 2);
+			assert_ok!(OwnedKittiesList::<Test>::append(&0, 2));
 
 			assert_eq!(OwnedKittiesTest::get(&(0, None)), Some(KittyLinkedItem::<Test> {
 				prev: Some(2),
@@ -314,7 +2084,7 @@ mod tests {
 				next: None,
 			}));
 
-			OwnedKittiesList::<Test>::append(&0, 3);
+			assert_ok!(OwnedKittiesList::<Test>::append(&0, 3));
 
 			assert_eq!(OwnedKittiesTest::get(&(0, None)), Some(KittyLinkedItem::<Test> {
 				prev: Some(3),
@@ -338,14 +2108,72 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn owned_kitties_written_before_counting_are_counted() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			// Earlier runtimes did not record the number of kitties of an account
+			<OwnedKittiesCount<Test>>::remove(&1);
+
+			assert_eq!(OwnedKittiesList::<Test>::len(&1), 2);
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+			assert_eq!(<OwnedKittiesCount<Test>>::get(&1), Some(1));
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn lengths_written_before_counting_are_recorded_over_blocks() {
+		let mut builder = ExtBuilder::default();
+		for owner in 1..=3 {
+			for _ in 0..60 {
+				builder = builder.kitty(owner);
+			}
+		}
+		with_externalities(&mut builder.build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+			KittyModule::on_free_balance_zero(&3);
+			// Earlier runtimes did not record the number of values in the lists
+			for owner in 1..=3 {
+				<OwnedKittiesCount<Test>>::remove(&owner);
+			}
+			<ShelterCount<DefaultInstance>>::remove(&());
+			<ListingsCount<DefaultInstance>>::remove(&());
+			<LengthsRecorded<Test>>::kill();
+
+			// Walking the list of account 1 and its next kitties uses up the steps of a block
+			run_to_block(2);
+			assert_eq!(<OwnedKittiesCount<Test>>::get(&1), Some(60));
+			assert_eq!(<OwnedKittiesCount<Test>>::get(&2), None);
+			assert_eq!(<NextLengthRecord<Test>>::get(), 40);
+			assert!(!KittyModule::lengths_recorded());
+
+			run_to_block(4);
+			assert_eq!(<OwnedKittiesCount<Test>>::get(&2), Some(60));
+			assert_eq!(<ShelterCount<DefaultInstance>>::get(&()), Some(60));
+			assert_eq!(<ListingsCount<DefaultInstance>>::get(&()), Some(1));
+			assert!(KittyModule::lengths_recorded());
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn owned_kitties_reject_duplicate_values() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(OwnedKittiesList::<Test>::append(&0, 1));
+
+			assert_noop!(OwnedKittiesList::<Test>::append(&0, 1), "Value is already in the list");
+			assert_eq!(OwnedKittiesList::<Test>::len(&0), 1);
+		});
+	}
+
 	#[test]
 	fn owned_kitties_can_remove_values() {
-		with_externalities(&mut new_test_ext(), || {
-			OwnedKittiesList::<Test>::append(&0, 1);
-			OwnedKittiesList::<Test>::append(&0, 2);
-			OwnedKittiesList::<Test>::append(&0, 3);
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(OwnedKittiesList::<Test>::append(&0, 1));
+			assert_ok!(OwnedKittiesList::<Test>::append(&0, 2));
+			assert_ok!(OwnedKittiesList::<Test>::append(&0, 3));
 
-			OwnedKittiesList::<Test>::remove(&0, 2);
+			assert_ok!(OwnedKittiesList::<Test>::remove(&0, 2));
 
 			assert_eq!(OwnedKittiesTest::get(&(0, None)), Some(KittyLinkedItem::<Test> {
 				prev: Some(3),
@@ -364,7 +2192,7 @@ mod tests {
 				next: None,
 			}));
 
-			OwnedKittiesList::<Test>::remove(&0, 1);
+			assert_ok!(OwnedKittiesList::<Test>::remove(&0, 1));
 
 			assert_eq!(OwnedKittiesTest::get(&(0, None)), Some(KittyLinkedItem::<Test> {
 				prev: Some(3),
@@ -380,7 +2208,7 @@ mod tests {
 				next: None,
 			}));
 
-			OwnedKittiesList::<Test>::remove(&0, 3);
+			assert_ok!(OwnedKittiesList::<Test>::remove(&0, 3));
 
 			assert_eq!(OwnedKittiesTest::get(&(0, None)), Some(KittyLinkedItem::<Test> {
 				prev: None,
@@ -394,4 +2222,1519 @@ mod tests {
 			assert_eq!(OwnedKittiesTest::get(&(0, Some(2))), None);
 		});
 	}
+
+	#[test]
+	fn reorder_works() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(1).kitty(2).build(), || {
+			assert_ok!(KittyModule::reorder(Origin::signed(1), 2, None));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Reordered(1, 2, None)));
+			assert_eq!(KittyModule::kitties_of(&1, None, 10), vec![2, 0, 1]);
+
+			assert_ok!(KittyModule::reorder(Origin::signed(1), 0, Some(1)));
+
+			assert_eq!(KittyModule::kitties_of(&1, None, 10), vec![2, 1, 0]);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+
+			assert_noop!(KittyModule::reorder(Origin::signed(1), 3, None), "Only owner can reorder kitty");
+			assert_noop!(KittyModule::reorder(Origin::signed(1), 0, Some(3)), "Only owner can reorder kitty");
+			assert_noop!(KittyModule::reorder(Origin::signed(1), 0, Some(0)), "Cannot move kitty after itself");
+		});
+	}
+
+	#[test]
+	fn kitties_of_returns_bounded_pages() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			for _ in 0..(MAX_KITTIES_PAGE + 5) {
+				assert_ok!(KittyModule::create(Origin::signed(1)));
+			}
+
+			assert_eq!(KittyModule::kitty_count_of(&1), MAX_KITTIES_PAGE + 5);
+			assert_eq!(KittyModule::kitties_of(&1, None, u32::max_value()).len() as u32, MAX_KITTIES_PAGE);
+			assert_eq!(KittyModule::kitties_of(&1, Some(MAX_KITTIES_PAGE - 1), 10), vec![100, 101, 102, 103, 104]);
+		});
+	}
+
+	#[test]
+	fn transfer_without_approval_setting_is_immediate() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::pending_transfer(0), None);
+		});
+	}
+
+	#[test]
+	fn transfer_accepts_account_index() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Index(2), 0));
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(3), 1));
+
+			assert_eq!(KittyModule::kitty_owner(0), Some(3));
+			assert_eq!(KittyModule::kitty_owner(1), Some(3));
+			assert!(OwnedKittiesTest::exists(&(3, Some(0))));
+			assert!(OwnedKittiesTest::exists(&(3, Some(1))));
+
+			assert_noop!(KittyModule::transfer(Origin::signed(3), Address::Index(9), 0), "invalid account index");
+		});
+	}
+
+	#[test]
+	fn pending_transfer_can_be_accepted() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::set_transfer_approval(Origin::signed(2), true));
+
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+
+			assert_eq!(KittyModule::pending_transfer(0), Some(2));
+			assert_eq!(KittyModule::kitty_owner(0), Some(1));
+			assert!(OwnedKittiesTest::exists(&(1, Some(0))));
+
+			assert_noop!(KittyModule::transfer(Origin::signed(1), Address::Id(3), 0), "Kitty has a pending transfer");
+			assert_noop!(KittyModule::ask(Origin::signed(1), 0, Some(10), None), "Kitty has a pending transfer");
+			assert_noop!(KittyModule::accept_transfer(Origin::signed(3), 0), "Not recipient of pending transfer");
+
+			assert_ok!(KittyModule::accept_transfer(Origin::signed(2), 0));
+
+			assert_eq!(KittyModule::pending_transfer(0), None);
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert!(!OwnedKittiesTest::exists(&(1, Some(0))));
+			assert!(OwnedKittiesTest::exists(&(2, Some(0))));
+		});
+	}
+
+	#[test]
+	fn pending_transfer_can_be_rejected_or_cancelled() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::set_transfer_approval(Origin::signed(2), true));
+
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+			assert_ok!(KittyModule::reject_transfer(Origin::signed(2), 0));
+
+			assert_eq!(KittyModule::pending_transfer(0), None);
+			assert_eq!(KittyModule::kitty_owner(0), Some(1));
+
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+			assert_noop!(KittyModule::cancel_transfer(Origin::signed(2), 0), "Only owner can cancel transfer");
+			assert_ok!(KittyModule::cancel_transfer(Origin::signed(1), 0));
+
+			assert_eq!(KittyModule::pending_transfer(0), None);
+			assert_eq!(KittyModule::kitty_owner(0), Some(1));
+			assert_noop!(KittyModule::accept_transfer(Origin::signed(2), 0), "No pending transfer");
+		});
+	}
+
+	#[test]
+	fn reaped_account_kitties_go_to_shelter() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 1, Some(10), None));
+
+			KittyModule::on_free_balance_zero(&1);
+
+			assert_eq!(KittyModule::kitty_owner(0), None);
+			assert_eq!(KittyModule::kitty_owner(1), None);
+			assert_eq!(KittyModule::kitty_price(1), None);
+			assert_eq!(OwnedKittiesTest::get(&(1, None)), None);
+			assert_eq!(KittyModule::shelter(&((), None)), Some(KittyLinkedItem::<Test> {
+				prev: Some(1),
+				next: Some(0),
+			}));
+			assert_eq!(ShelterList::<Test>::len(&()), 2);
+
+			assert_ok!(KittyModule::adopt(Origin::signed(2), 0));
+
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert!(OwnedKittiesTest::exists(&(2, Some(0))));
+			assert_eq!(KittyModule::shelter(&((), Some(0))), None);
+			assert_noop!(KittyModule::adopt(Origin::signed(3), 0), "Kitty is not in the shelter");
+		});
+	}
+
+	#[test]
+	fn corrupted_lists_do_not_lose_kitties() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			// Kitty 1 is also in the shelter
+			assert_ok!(ShelterList::<Test>::append(&(), 1));
+
+			KittyModule::on_free_balance_zero(&1);
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::NotSheltered(1, 1)));
+			assert_eq!(KittyModule::kitty_owner(0), None);
+			assert_eq!(KittyModule::kitty_owner(1), Some(1));
+			assert_eq!(KittyModule::kitties_of(&1, None, 10), vec![1]);
+
+			// Kitty 0 is also in the list of account 2
+			assert_ok!(OwnedKittiesList::<Test>::append(&2, 0));
+			assert_noop!(KittyModule::adopt(Origin::signed(2), 0), "Value is already in the list");
+			assert!(KittyModule::shelter(&((), Some(0))).is_some());
+		});
+	}
+
+	#[test]
+	fn block_reaping_keeps_kitties_with_their_owner() {
+		with_externalities(&mut ExtBuilder::default().existential_deposit(10).build(), || {
+			assert_ok!(PuppyModule::create(Origin::signed(1)));
+			assert_ok!(PuppyModule::create(Origin::signed(1)));
+
+			// The reap lock keeps the existential deposit in the account
+			assert!(Balances::transfer(Origin::signed(1), Address::Id(2), 95).is_err());
+			assert_ok!(Balances::transfer(Origin::signed(1), Address::Id(2), 90));
+			assert_eq!(Balances::free_balance(&1), 10);
+			assert_eq!(PuppyModule::kitty_owner(0), Some(1));
+			assert_eq!(PuppyModule::kitty_count_of(&1), 2);
+
+			// The lock goes with the last kitty
+			assert_ok!(PuppyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+			assert!(Balances::transfer(Origin::signed(1), Address::Id(2), 10).is_err());
+			assert_ok!(PuppyModule::transfer(Origin::signed(1), Address::Id(2), 1));
+			assert_ok!(Balances::transfer(Origin::signed(1), Address::Id(2), 10));
+			assert_eq!(Balances::free_balance(&1), 0);
+			assert_eq!(PuppyModule::kitty_count_of(&2), 2);
+		});
+	}
+
+	#[test]
+	fn slashed_account_kitties_go_to_shelter_under_block_reaping() {
+		with_externalities(&mut ExtBuilder::default().existential_deposit(10).build(), || {
+			assert_ok!(PuppyModule::create(Origin::signed(1)));
+
+			// Slashing ignores locks, so the account is still reaped
+			let _ = <Balances as Currency<u64>>::slash(&1, 100);
+			assert_eq!(Balances::free_balance(&1), 0);
+			assert_eq!(PuppyModule::kitty_owner(0), None);
+			assert!(PuppyModule::shelter(&((), Some(0))).is_some());
+			assert_eq!(Balances::locks(&1), vec![]);
+		});
+	}
+
+	#[test]
+	fn locked_kitties_stay_with_reaped_account() {
+		with_externalities(&mut ExtBuilder::default().existential_deposit(10).build(), || {
+			assert_ok!(PuppyModule::create(Origin::signed(1)));
+			assert_ok!(PuppyModule::create(Origin::signed(1)));
+			assert_ok!(<PuppyModule as NonFungibleAsset<u64>>::lock(0));
+
+			let _ = <Balances as Currency<u64>>::slash(&1, 100);
+			assert_eq!(PuppyModule::kitty_owner(0), Some(1));
+			assert_eq!(PuppyModule::kitty_owner(1), None);
+			assert!(PuppyModule::shelter(&((), Some(0))).is_none());
+
+			// The module holding the lock can still move the kitty
+			assert_ok!(<PuppyModule as NonFungibleAsset<u64>>::unlock(0));
+			assert_ok!(<PuppyModule as NonFungibleAsset<u64>>::transfer(&1, &2, 0));
+			assert_eq!(PuppyModule::kitty_owner(0), Some(2));
+			assert_eq!(PuppyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn admin_calls_require_root() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+
+			assert_noop!(KittyModule::force_transfer(Origin::signed(1), Address::Id(2), 0), "bad origin: expected to be a root origin");
+			assert_noop!(KittyModule::freeze(Origin::signed(1), 0), "bad origin: expected to be a root origin");
+			assert_noop!(KittyModule::set_market_paused(Origin::signed(1), true), "bad origin: expected to be a root origin");
+			assert_noop!(KittyModule::set_minimum_price(Origin::signed(1), 10), "bad origin: expected to be a root origin");
+			assert_noop!(KittyModule::set_max_kitties_per_account(Origin::signed(1), 1), "bad origin: expected to be a root origin");
+			assert_noop!(KittyModule::set_breeding_fees(Origin::signed(1), 1, 1, 1), "bad origin: expected to be a root origin");
+		});
+	}
+
+	#[test]
+	fn root_can_force_transfer() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+			assert_ok!(KittyModule::set_transfer_approval(Origin::signed(2), true));
+
+			assert_ok!(KittyModule::force_transfer(Origin::ROOT, Address::Id(2), 0));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Transferred(1, 2, 0)));
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::kitty_price(0), None);
+			assert!(OwnedKittiesTest::exists(&(2, Some(0))));
+			assert!(!OwnedKittiesTest::exists(&(1, Some(0))));
+		});
+	}
+
+	#[test]
+	fn frozen_kitty_cannot_be_transferred_bred_or_listed() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+
+			assert_ok!(KittyModule::freeze(Origin::ROOT, 0));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Frozen(0)));
+			assert_eq!(KittyModule::kitty_price(0), None);
+			assert_noop!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0), "Kitty is frozen");
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 1), "Kitty is frozen");
+			assert_noop!(KittyModule::ask(Origin::signed(1), 0, Some(10), None), "Kitty is frozen");
+
+			assert_ok!(KittyModule::unfreeze(Origin::ROOT, 0));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Unfrozen(0)));
+			assert_noop!(KittyModule::unfreeze(Origin::ROOT, 0), "Kitty is not frozen");
+
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+		});
+	}
+
+	#[test]
+	fn paused_market_blocks_ask_and_buy() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+
+			assert_ok!(KittyModule::set_market_paused(Origin::ROOT, true));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::MarketPauseSet(true)));
+
+			assert_noop!(KittyModule::ask(Origin::signed(1), 0, Some(20), None), "Market is paused");
+			assert_noop!(KittyModule::buy(Origin::signed(2), 0, 10), "Market is paused");
+			// Delisting is still possible
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, None, None));
+
+			assert_ok!(KittyModule::set_market_paused(Origin::ROOT, false));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::MarketPauseSet(false)));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(20), None));
+		});
+	}
+
+	#[test]
+	fn minimum_price_is_enforced_on_ask() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+
+			assert_ok!(KittyModule::set_minimum_price(Origin::ROOT, 10));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::MinimumPriceSet(10)));
+
+			assert_noop!(KittyModule::ask(Origin::signed(1), 0, Some(9), None), "Price is below minimum");
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+		});
+	}
+
+	#[test]
+	fn root_can_change_the_account_limit() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_eq!(KittyModule::max_kitties_per_account(), u32::max_value());
+
+			assert_ok!(KittyModule::set_max_kitties_per_account(Origin::ROOT, 2));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::MaxKittiesPerAccountSet(2)));
+			assert_eq!(KittyModule::max_kitties_per_account(), 2);
+
+			assert_noop!(KittyModule::create(Origin::signed(1)), "Account has too many kitties");
+			assert_ok!(KittyModule::create(Origin::signed(2)));
+			// The other collection keeps its own limit
+			assert_ok!(PuppyModule::create(Origin::signed(1)));
+		});
+	}
+
+	#[test]
+	fn root_can_change_the_breeding_fees() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_eq!(KittyModule::breeding_fee_for(1), 5 + 2 + 10);
+
+			assert_ok!(KittyModule::set_breeding_fees(Origin::ROOT, 1, 2, 3));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::BreedingFeesSet(1, 2, 3)));
+			assert_eq!(KittyModule::breeding_fee_for(1), 1 + 2 * 2 + 3);
+
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			assert_eq!(Balances::free_balance(&1), 100 - (1 + 2 * 2));
+		});
+	}
+
+	#[test]
+	fn create_works() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Created(1, 0)));
+			assert!(KittyModule::kitty(0).is_some());
+			assert_eq!(KittyModule::kitties_count(), 1);
+			assert_eq!(KittyModule::kitty_owner(0), Some(1));
+			assert_eq!(OwnedKittiesTest::get(&(1, None)), Some(KittyLinkedItem::<Test> {
+				prev: Some(0),
+				next: Some(0),
+			}));
+		});
+	}
+
+	#[test]
+	fn create_fails_on_overflow() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			<KittiesCount<Test>>::put(u32::max_value());
+
+			assert_noop!(KittyModule::create(Origin::signed(1)), "Kitties count overflow");
+		});
+	}
+
+	#[test]
+	fn breed_works() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			run_to_block(3);
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Hatched(1, 0, 2)));
+			assert!(KittyModule::kitty(2).is_some());
+			assert_eq!(KittyModule::kitties_count(), 3);
+			assert_eq!(KittyModule::kitty_owner(2), Some(1));
+			assert!(OwnedKittiesTest::exists(&(1, Some(2))));
+		});
+	}
+
+	#[test]
+	fn breeding_lays_an_egg_that_hatches() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::EggLaid(1, 0, 3)));
+			let egg = KittyModule::egg(0).expect("Egg expected");
+			assert_eq!(egg.owner, 1);
+			assert_eq!(egg.parents, (0, 1));
+			assert_eq!(egg.generation, 1);
+			assert_eq!(egg.hatch_at, 3);
+			assert_eq!(KittyModule::eggs_of(&1), vec![0]);
+			assert_eq!(KittyModule::eggs_hatching_at(3), vec![0]);
+			assert_eq!(KittyModule::kitties_count(), 2);
+
+			// The parents are locked until the egg hatches
+			assert!(KittyModule::is_locked(0));
+			assert!(KittyModule::is_locked(1));
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 1), "Kitty is locked");
+			assert_noop!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0), "Kitty is locked");
+
+			run_to_block(2);
+			assert!(KittyModule::egg(0).is_some());
+
+			run_to_block(3);
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Hatched(1, 0, 2)));
+			assert!(System::events().iter().any(|record| record.event == TestEvent::kitties(RawEvent::Created(1, 2))));
+			assert_eq!(KittyModule::kitty(2).map(|kitty| kitty.0), Some(egg.dna));
+			assert_eq!(KittyModule::kitty_owner(2), Some(1));
+			assert_eq!(KittyModule::kitty_generation(2), 1);
+			assert_eq!(KittyModule::egg(0), None);
+			assert_eq!(KittyModule::eggs_of(&1), Vec::<EggId>::new());
+			assert_eq!(KittyModule::eggs_hatching_at(3), Vec::<EggId>::new());
+			assert!(!KittyModule::is_locked(0));
+			assert!(!KittyModule::is_locked(1));
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn reaped_account_eggs_hatch_into_the_shelter() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			let egg = KittyModule::egg(0).expect("Egg expected");
+
+			KittyModule::on_free_balance_zero(&1);
+
+			assert!(System::events().iter().any(|record| record.event == TestEvent::kitties(RawEvent::Hatched(1, 0, 2))));
+			assert_eq!(KittyModule::kitty(2).map(|kitty| kitty.0), Some(egg.dna));
+			assert_eq!(KittyModule::kitty_generation(2), 1);
+			assert_eq!(KittyModule::egg(0), None);
+			assert!(!<OwnedEggs<Test>>::exists(&1));
+			// The unlocked parents go to the shelter with the kitty
+			assert_eq!(ShelterList::<Test>::iter(&()).collect::<Vec<_>>(), vec![2, 0, 1]);
+			assert!(!KittyModule::is_locked(0));
+			assert!(!KittyModule::is_locked(1));
+
+			run_to_block(3);
+			assert_eq!(KittyModule::kitties_count(), 3);
+			assert_ok!(KittyModule::adopt(Origin::signed(2), 2));
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn parents_of_reaped_account_go_to_the_shelter_when_the_egg_hatches() {
+		with_externalities(&mut ExtBuilder::default().existential_deposit(10).kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 0, Address::Id(2), 10, 1, true));
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 1, Address::Id(2), 10, 1, true));
+			assert_ok!(KittyModule::breed(Origin::signed(2), 0, 1));
+			assert_eq!(KittyModule::eggs_of(&2), vec![0]);
+
+			// The locked parents stay with the reaped owner
+			let _ = <Balances as Currency<u64>>::slash(&1, 100);
+			assert_eq!(KittyModule::kitty_owner(0), Some(1));
+			assert_eq!(KittyModule::kitty_owner(1), Some(1));
+			assert!(KittyModule::is_stranded(0));
+			assert!(KittyModule::is_stranded(1));
+
+			run_to_block(3);
+			assert_eq!(KittyModule::kitty_owner(2), Some(2));
+			assert_eq!(KittyModule::kitty_owner(0), None);
+			assert_eq!(KittyModule::kitty_owner(1), None);
+			assert_eq!(ShelterList::<Test>::iter(&()).collect::<Vec<_>>(), vec![0, 1]);
+			assert!(!KittyModule::is_stranded(0));
+			assert!(!KittyModule::is_stranded(1));
+			assert_eq!(KittyModule::kitty_count_of(&1), 0);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn egg_that_cannot_hatch_is_discarded() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			// The next kitty is already in the list of the owner, so the egg cannot hatch
+			assert_ok!(OwnedKittiesList::<Test>::append(&1, 2));
+
+			run_to_block(3 + u64::from(MAX_HATCH_ATTEMPTS) - 2);
+			assert!(KittyModule::egg(0).is_some());
+			assert!(KittyModule::is_locked(0));
+
+			run_to_block(3 + u64::from(MAX_HATCH_ATTEMPTS) - 1);
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::EggDiscarded(1, 0)));
+			assert_eq!(KittyModule::egg(0), None);
+			assert_eq!(KittyModule::eggs_of(&1), Vec::<EggId>::new());
+			assert!(!KittyModule::is_locked(0));
+			assert!(!KittyModule::is_locked(1));
+			assert_eq!(KittyModule::kitties_count(), 2);
+		});
+	}
+
+	#[test]
+	fn eggs_can_be_transferred() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+
+			assert_noop!(KittyModule::transfer_egg(Origin::signed(2), Address::Id(3), 0), "Only owner can transfer egg");
+			assert_noop!(KittyModule::transfer_egg(Origin::signed(1), Address::Id(1), 0), "Cannot transfer egg to yourself");
+			assert_noop!(KittyModule::transfer_egg(Origin::signed(1), Address::Id(2), 1), "Egg does not exist");
+			assert_ok!(KittyModule::transfer_egg(Origin::signed(1), Address::Id(2), 0));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::EggTransferred(1, 2, 0)));
+			assert_eq!(KittyModule::egg(0).map(|egg| egg.owner), Some(2));
+			assert_eq!(KittyModule::eggs_of(&1), Vec::<EggId>::new());
+			assert_eq!(KittyModule::eggs_of(&2), vec![0]);
+
+			run_to_block(3);
+			assert_eq!(KittyModule::kitty_owner(2), Some(2));
+			assert_eq!(KittyModule::kitties_of(&2, None, 10), vec![2]);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn eggs_count_towards_account_limit() {
+		with_externalities(&mut ExtBuilder::default().max_kitties_per_account(3).kitty(1).kitty(1).kitty(2).kitty(2).kitty(2).build(), || {
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+
+			assert_noop!(KittyModule::create(Origin::signed(1)), "Account has too many kitties");
+			assert_noop!(KittyModule::transfer(Origin::signed(2), Address::Id(1), 2), "Account has too many kitties");
+			assert_noop!(KittyModule::transfer_egg(Origin::signed(1), Address::Id(2), 0), "Account has too many kitties");
+
+			run_to_block(3);
+			assert_eq!(KittyModule::kitty_count_of(&1), 3);
+		});
+	}
+
+	#[test]
+	fn egg_hatches_later_when_no_kitty_id_is_left() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			<KittiesCount<Test>>::put(u32::max_value());
+
+			run_to_block(3);
+			assert!(KittyModule::egg(0).is_some());
+			assert_eq!(KittyModule::eggs_hatching_at(4), vec![0]);
+			assert!(KittyModule::is_locked(0));
+
+			<KittiesCount<Test>>::put(2);
+			run_to_block(4);
+			assert_eq!(KittyModule::egg(0), None);
+			assert_eq!(KittyModule::kitty_owner(2), Some(1));
+		});
+	}
+
+	#[test]
+	fn delegate_breeds_for_owner() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 0, Address::Id(2), 10, 2, false));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::BreedingDelegated(1, 0, 2, 10, 2)));
+			assert_noop!(KittyModule::breed(Origin::signed(2), 0, 1), "Not owner of kitty2");
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 1, Address::Id(2), 10, 1, false));
+
+			// The delegate pays the fee and the owner gets the egg
+			assert_ok!(KittyModule::breed(Origin::signed(2), 0, 1));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::EggLaid(1, 0, 3)));
+			assert_eq!(KittyModule::eggs_of(&1), vec![0]);
+			assert_eq!(Balances::free_balance(&1), 100);
+			assert_eq!(Balances::free_balance(&2), 93);
+			assert_eq!(KittyModule::breeding_delegation(0).map(|d| d.uses_left), Some(1));
+			assert_eq!(KittyModule::breeding_delegation(1), None);
+
+			run_to_block(3);
+			assert_eq!(KittyModule::kitty_owner(2), Some(1));
+			assert_noop!(KittyModule::breed(Origin::signed(2), 0, 1), "Not owner of kitty2");
+		});
+	}
+
+	#[test]
+	fn offspring_goes_to_delegate_if_both_delegations_say_so() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 0, Address::Id(2), 10, 5, true));
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 1, Address::Id(2), 10, 5, false));
+			assert_ok!(KittyModule::breed(Origin::signed(2), 0, 1));
+			assert_eq!(KittyModule::egg(0).map(|egg| egg.owner), Some(1));
+
+			run_to_block(3);
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 1, Address::Id(2), 10, 5, true));
+			assert_ok!(KittyModule::breed(Origin::signed(2), 0, 1));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::EggLaid(2, 1, 5)));
+			assert_eq!(KittyModule::eggs_of(&2), vec![1]);
+
+			run_to_block(5);
+			assert_eq!(KittyModule::kitty_owner(3), Some(2));
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn breeding_delegation_is_limited() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(2).build(), || {
+			assert_noop!(KittyModule::delegate_breeding(Origin::signed(2), 0, Address::Id(3), 10, 1, false), "Only owner can delegate breeding");
+			assert_noop!(KittyModule::delegate_breeding(Origin::signed(1), 0, Address::Id(1), 10, 1, false), "Cannot delegate breeding to yourself");
+			assert_noop!(KittyModule::delegate_breeding(Origin::signed(1), 0, Address::Id(3), 10, 0, false), "Delegation has no uses");
+			System::set_block_number(2);
+			assert_noop!(KittyModule::delegate_breeding(Origin::signed(1), 0, Address::Id(3), 1, 1, false), "Delegation is already expired");
+
+			// Both parents must have the same owner
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 0, Address::Id(2), 4, 1, false));
+			assert_noop!(KittyModule::breed(Origin::signed(2), 0, 2), "Parents have different owners");
+
+			// The delegation can be used until its expiry block
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 1, Address::Id(2), 4, 1, false));
+			System::set_block_number(5);
+			assert_noop!(KittyModule::breed(Origin::signed(2), 0, 1), "Not onwer of kitty1");
+		});
+	}
+
+	#[test]
+	fn breeding_delegation_is_cleared_on_transfer_and_revoke() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 0, Address::Id(2), 10, 1, false));
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(3), 0));
+			assert_eq!(KittyModule::breeding_delegation(0), None);
+
+			assert_ok!(KittyModule::delegate_breeding(Origin::signed(1), 1, Address::Id(2), 10, 1, false));
+			assert_noop!(KittyModule::revoke_breeding_delegation(Origin::signed(2), 1), "Only owner can delegate breeding");
+			assert_ok!(KittyModule::revoke_breeding_delegation(Origin::signed(1), 1));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::BreedingDelegationRevoked(1, 1)));
+			assert_eq!(KittyModule::breeding_delegation(1), None);
+			assert_noop!(KittyModule::revoke_breeding_delegation(Origin::signed(1), 1), "Kitty has no breeding delegation");
+		});
+	}
+
+	#[test]
+	fn kitties_born_before_ageing_count_as_adults_at_the_upgrade() {
+		with_externalities(&mut ExtBuilder::default().life_stages(10, 20, Some(50)).kitty(1).kitty(1).build(), || {
+			// Earlier runtimes did not record births
+			<KittyBirths<Test>>::remove(0);
+			<KittyBirths<Test>>::remove(1);
+			<AgeingSince<Test>>::kill();
+			System::set_block_number(100);
+
+			run_to_block(101);
+			assert_eq!(KittyModule::ageing_since(), Some(101));
+			assert_eq!(KittyModule::kitty_age(0), 10);
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Adult));
+			// So existing kitties can still breed
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+
+			run_to_block(121);
+			assert_eq!(KittyModule::ageing_since(), Some(101));
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Elder));
+		});
+	}
+
+	#[test]
+	fn kitties_age_through_life_stages() {
+		with_externalities(&mut ExtBuilder::default().life_stages(10, 20, Some(50)).kitty(1).build(), || {
+			assert_eq!(KittyModule::kitty_age(0), 0);
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Kitten));
+			assert_eq!(KittyModule::life_stage(1), None);
+
+			System::set_block_number(10);
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Kitten));
+			System::set_block_number(11);
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Adult));
+			System::set_block_number(30);
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Adult));
+			System::set_block_number(31);
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Elder));
+			System::set_block_number(51);
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Legendary));
+
+			// Without a lifespan elders stay elders
+			LIFESPAN.with(|v| *v.borrow_mut() = None);
+			assert_eq!(KittyModule::life_stage(0), Some(LifeStage::Elder));
+		});
+	}
+
+	#[test]
+	fn only_adults_can_breed() {
+		let builder = ExtBuilder::default()
+			.life_stages(10, 20, Some(50))
+			.kitty(1).kitty(1)
+			.kitty(1).kitty(1);
+		with_externalities(&mut builder.build(), || {
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 1), "Only adults can breed");
+
+			System::set_block_number(11);
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			// Hatched kitties are born when they hatch
+			run_to_block(13);
+			assert_eq!(KittyModule::kitty_age(4), 0);
+			assert_eq!(KittyModule::life_stage(4), Some(LifeStage::Kitten));
+			assert_noop!(KittyModule::breed(Origin::signed(1), 2, 4), "Only adults can breed");
+
+			System::set_block_number(31);
+			assert_noop!(KittyModule::breed(Origin::signed(1), 2, 3), "Only adults can breed");
+
+			// Legendary kitties cannot breed but are still traded
+			System::set_block_number(51);
+			assert_eq!(KittyModule::life_stage(2), Some(LifeStage::Legendary));
+			assert_noop!(KittyModule::breed(Origin::signed(1), 2, 3), "Only adults can breed");
+			assert_ok!(KittyModule::ask(Origin::signed(1), 2, Some(10), None));
+			assert_ok!(KittyModule::buy(Origin::signed(2), 2, 10));
+			assert_eq!(KittyModule::kitty_owner(2), Some(2));
+		});
+	}
+
+	#[test]
+	fn appearance_depends_on_life_stage() {
+		// Pre-minted kitty 9 has all its DNA bytes set to 9
+		let builder = ExtBuilder::default().life_stages(10, 20, Some(50));
+		let builder = (0..10).fold(builder, |builder, _| builder.kitty(1));
+		with_externalities(&mut builder.build(), || {
+			assert_eq!(KittyModule::appearance(10), None);
+			assert_eq!(KittyModule::appearance(9), Some(Appearance {
+				stage: LifeStage::Kitten,
+				body_color: 9,
+				eye_color: 9,
+				pattern: None,
+				size: 4,
+				glow: false,
+			}));
+
+			System::set_block_number(11);
+			assert_eq!(KittyModule::appearance(9), Some(Appearance {
+				stage: LifeStage::Adult,
+				body_color: 9,
+				eye_color: 9,
+				pattern: Some(9),
+				size: 9,
+				glow: false,
+			}));
+
+			System::set_block_number(31);
+			assert_eq!(KittyModule::appearance(9).map(|a| (a.stage, a.body_color, a.glow)), Some((LifeStage::Elder, 4, false)));
+
+			System::set_block_number(51);
+			assert_eq!(KittyModule::appearance(9).map(|a| (a.stage, a.body_color, a.glow)), Some((LifeStage::Legendary, 4, true)));
+		});
+	}
+
+	#[test]
+	fn breed_fails_for_invalid_parents() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(2).build(), || {
+			assert_noop!(KittyModule::breed(Origin::signed(1), 5, 1), "Invalid kitty_id_1");
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 5), "Invalid kitty_id_2");
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 0), "Needs different parent");
+			assert_noop!(KittyModule::breed(Origin::signed(1), 2, 0), "Not onwer of kitty1");
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 2), "Not owner of kitty2");
+		});
+	}
+
+	#[test]
+	fn breed_fails_on_overflow() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			<KittiesCount<Test>>::put(u32::max_value());
+
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 1), "Kitties count overflow");
+		});
+	}
+
+	#[test]
+	fn transfer_works() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Transferred(1, 2, 0)));
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert!(!OwnedKittiesTest::exists(&(1, Some(0))));
+			assert!(OwnedKittiesTest::exists(&(1, Some(1))));
+			assert!(OwnedKittiesTest::exists(&(2, Some(0))));
+		});
+	}
+
+	#[test]
+	fn transfer_fails_for_non_owner() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_noop!(KittyModule::transfer(Origin::signed(2), Address::Id(3), 0), "Only owner can transfer kitty");
+			assert_noop!(KittyModule::transfer(Origin::signed(2), Address::Id(3), 1), "Only owner can transfer kitty");
+		});
+	}
+
+	#[test]
+	fn transfer_to_self_fails() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_noop!(KittyModule::transfer(Origin::signed(1), Address::Id(1), 0), "Cannot transfer kitty to yourself");
+		});
+	}
+
+	#[test]
+	fn ask_works() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Ask(1, 0, Some(10))));
+			assert_eq!(KittyModule::kitty_price(0), Some(10));
+
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, None, None));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Ask(1, 0, None)));
+			assert_eq!(KittyModule::kitty_price(0), None);
+		});
+	}
+
+	#[test]
+	fn ask_fails_for_non_owner() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_noop!(KittyModule::ask(Origin::signed(2), 0, Some(10), None), "Only owner can set price for kitty");
+		});
+	}
+
+	#[test]
+	fn buy_works() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+
+			assert_ok!(KittyModule::buy(Origin::signed(2), 0, 15));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Sold(1, 2, 0, 10)));
+			assert_eq!(Balances::free_balance(&1), 110);
+			assert_eq!(Balances::free_balance(&2), 90);
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::kitty_price(0), None);
+			assert!(OwnedKittiesTest::exists(&(2, Some(0))));
+		});
+	}
+
+	#[test]
+	fn buy_fails_for_missing_or_unlisted_kitty() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_noop!(KittyModule::buy(Origin::signed(2), 1, 10), "Kitty does not exist");
+			assert_noop!(KittyModule::buy(Origin::signed(2), 0, 10), "Kitty not for sale");
+		});
+	}
+
+	#[test]
+	fn buy_fails_when_price_too_low() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+
+			assert_noop!(KittyModule::buy(Origin::signed(2), 0, 9), "Price is too low");
+		});
+	}
+
+	#[test]
+	fn buy_fails_with_insufficient_balance() {
+		with_externalities(&mut ExtBuilder::default().balances(vec![(1, 100), (2, 5)]).kitty(1).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+
+			assert_noop!(KittyModule::buy(Origin::signed(2), 0, 10), "too few free funds in account");
+		});
+	}
+
+	#[test]
+	fn buy_keeps_the_buyer_alive() {
+		with_externalities(&mut ExtBuilder::default().existential_deposit(10).kitty(1).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(100), None));
+
+			// The kitty would go to a reaped account
+			assert_noop!(KittyModule::buy(Origin::signed(2), 0, 100), "payment would kill account");
+
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(90), None));
+			assert_ok!(KittyModule::buy(Origin::signed(2), 0, 90));
+			assert_eq!(Balances::free_balance(&2), 10);
+			assert_eq!(Balances::free_balance(&1), 190);
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn integrity_check_passes_after_operations() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(2).build(), || {
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			run_to_block(3);
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 1));
+			assert_ok!(KittyModule::ask(Origin::signed(2), 2, Some(10), None));
+			assert_ok!(KittyModule::buy(Origin::signed(3), 2, 10));
+			KittyModule::on_free_balance_zero(&1);
+			assert_ok!(KittyModule::adopt(Origin::signed(3), 0));
+
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn integrity_check_reports_discrepancies() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			<KittyOwners<Test>>::remove(0);
+			OwnedKittiesTest::insert(&(1, Some(1)), KittyLinkedItem::<Test> {
+				prev: None,
+				next: None,
+			});
+
+			assert_eq!(KittyModule::check_integrity(), vec![
+				IntegrityError::Unowned(0),
+				IntegrityError::BrokenOwnerList(1, LinkError::AsymmetricLink(1)),
+				IntegrityError::OwnerMismatch(1, 0),
+			]);
+		});
+	}
+
+	#[test]
+	fn transfer_fails_when_owner_list_is_corrupted() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			// Kitty 0 of account 1 is also in the list of account 2
+			assert_ok!(OwnedKittiesList::<Test>::append(&2, 0));
+
+			assert_noop!(KittyModule::force_transfer(Origin::ROOT, Address::Id(2), 0), "Kitty is already owned by recipient");
+		});
+	}
+
+	#[test]
+	fn listings_are_ordered_by_price() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(2).kitty(2).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(30), None));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 1, Some(10), None));
+			assert_ok!(KittyModule::ask(Origin::signed(2), 2, Some(20), None));
+			assert_ok!(KittyModule::ask(Origin::signed(2), 3, Some(10), None));
+
+			assert_eq!(KittyModule::listings_page(None, 10), vec![(1, 10), (3, 10), (2, 20), (0, 30)]);
+			assert_eq!(KittyModule::cheapest_kitty(), Some((1, 10)));
+
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(5), None));
+			assert_eq!(KittyModule::listings_page(None, 10), vec![(0, 5), (1, 10), (3, 10), (2, 20)]);
+			assert_eq!(KittyModule::listings_page(Some(1), 2), vec![(3, 10), (2, 20)]);
+
+			assert_ok!(KittyModule::buy(Origin::signed(3), 0, 5));
+			assert_ok!(KittyModule::ask(Origin::signed(2), 3, None, None));
+			assert_ok!(KittyModule::transfer(Origin::signed(2), Address::Id(1), 2));
+
+			assert_eq!(KittyModule::listings_page(None, 10), vec![(1, 10)]);
+			assert_eq!(KittyModule::kitty_price(2), None);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+
+			assert_ok!(KittyModule::freeze(Origin::ROOT, 1));
+			assert_eq!(KittyModule::cheapest_kitty(), None);
+		});
+	}
+
+	#[test]
+	fn listings_are_placed_within_a_bounded_walk() {
+		let count = MAX_LISTING_STEPS + 2;
+		let mut builder = ExtBuilder::default().max_kitties_per_account(count);
+		for _ in 0..count {
+			builder = builder.kitty(1);
+		}
+		with_externalities(&mut builder.build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+			for kitty_id in 1..=MAX_LISTING_STEPS {
+				assert_ok!(KittyModule::ask(Origin::signed(1), kitty_id, Some(10), Some(kitty_id - 1)));
+			}
+
+			// Equal prices keep the order of the asks, so the new listing goes after all of them
+			let last = MAX_LISTING_STEPS + 1;
+			assert_noop!(KittyModule::ask(Origin::signed(1), last, Some(10), None), "Too many values to walk from the hint");
+			assert_noop!(KittyModule::ask(Origin::signed(1), 0, Some(20), None), "Too many values to walk from the hint");
+			assert_noop!(KittyModule::ask(Origin::signed(1), last, Some(10), Some(last)), "Hint is the value itself");
+			assert_noop!(KittyModule::ask(Origin::signed(1), last, Some(5), Some(0)), "Hint goes after the value");
+
+			assert_ok!(KittyModule::ask(Origin::signed(1), last, Some(10), Some(MAX_LISTING_STEPS)));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(5), None));
+			assert_eq!(KittyModule::listings_page(None, 2), vec![(0, 5), (1, 10)]);
+			assert_eq!(KittyModule::listings_page(Some(MAX_LISTING_STEPS), 2), vec![(last, 10)]);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn integrity_check_reports_unsorted_listings() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 1, Some(20), None));
+
+			<KittyPrices<Test>>::insert(0, 30);
+
+			assert_eq!(KittyModule::check_integrity(), vec![
+				IntegrityError::BrokenListings(LinkError::OutOfOrder(1)),
+			]);
+		});
+	}
+
+	#[test]
+	fn set_name_reserves_deposit_per_byte() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(2).build(), || {
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Named(1, 0, b"Tom".to_vec())));
+			assert_eq!(KittyModule::kitty_name(0), Some(b"Tom".to_vec()));
+			assert_eq!(KittyModule::named_kitty(b"Tom".to_vec()), Some(0));
+			assert_eq!(KittyModule::name_deposit(0), 30);
+			assert_eq!(Balances::reserved_balance(&1), 30);
+			assert_eq!(Balances::free_balance(&1), 70);
+
+			// Renaming frees the old name and adjusts the deposit
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tommy".to_vec()));
+			assert_eq!(KittyModule::named_kitty(b"Tom".to_vec()), None);
+			assert_eq!(KittyModule::named_kitty(b"Tommy".to_vec()), Some(0));
+			assert_eq!(Balances::reserved_balance(&1), 50);
+
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Al".to_vec()));
+			assert_eq!(Balances::reserved_balance(&1), 20);
+			assert_eq!(Balances::free_balance(&1), 80);
+		});
+	}
+
+	#[test]
+	fn set_name_fails_for_invalid_or_taken_names() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(2).build(), || {
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+
+			assert_noop!(KittyModule::set_name(Origin::signed(2), 1, b"Tom".to_vec()), "Name is already taken");
+			assert_noop!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()), "Name is already taken");
+			assert_noop!(KittyModule::set_name(Origin::signed(1), 1, b"Jerry".to_vec()), "Only owner can name kitty");
+			assert_noop!(KittyModule::set_name(Origin::signed(2), 1, vec![]), "Name is empty");
+			assert_noop!(KittyModule::set_name(Origin::signed(2), 1, b"Tom Jerry".to_vec()), "Name is too long");
+			assert_noop!(KittyModule::set_name(Origin::signed(2), 1, vec![0xff, 0xfe]), "Name is not valid UTF-8");
+
+			// 8 bytes need 80, account 2 only has 70 free
+			assert_ok!(Balances::transfer(Origin::signed(2), Address::Id(3), 30));
+			assert!(KittyModule::set_name(Origin::signed(2), 1, b"Tom Cat!".to_vec()).is_err());
+			assert_eq!(KittyModule::kitty_name(1), None);
+			assert_eq!(KittyModule::named_kitty(b"Tom Cat!".to_vec()), None);
+		});
+	}
+
+	#[test]
+	fn clear_name_refunds_deposit() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_noop!(KittyModule::clear_name(Origin::signed(1), 0), "Kitty has no name");
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+			assert_noop!(KittyModule::clear_name(Origin::signed(2), 0), "Only owner can name kitty");
+
+			assert_ok!(KittyModule::clear_name(Origin::signed(1), 0));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::NameCleared(1, 0)));
+			assert_eq!(KittyModule::kitty_name(0), None);
+			assert_eq!(KittyModule::named_kitty(b"Tom".to_vec()), None);
+			assert_eq!(KittyModule::name_deposit(0), 0);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 100);
+
+			// The name can be taken again
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+		});
+	}
+
+	#[test]
+	fn name_moves_with_kitty_on_transfer() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+
+			assert_eq!(KittyModule::kitty_name(0), Some(b"Tom".to_vec()));
+			assert_eq!(KittyModule::named_kitty(b"Tom".to_vec()), Some(0));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 100);
+			assert_eq!(Balances::reserved_balance(&2), 30);
+			assert_eq!(Balances::free_balance(&2), 70);
+
+			assert_ok!(KittyModule::clear_name(Origin::signed(2), 0));
+			assert_eq!(Balances::free_balance(&2), 100);
+		});
+	}
+
+	#[test]
+	fn name_is_released_when_buyer_cannot_cover_deposit() {
+		with_externalities(&mut ExtBuilder::default().balances(vec![(1, 100), (2, 40)]).kitty(1).build(), || {
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(20), None));
+
+			// Account 2 has 20 left after paying, short of the deposit of 30
+			assert_ok!(KittyModule::buy(Origin::signed(2), 0, 20));
+
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::kitty_name(0), None);
+			assert_eq!(KittyModule::named_kitty(b"Tom".to_vec()), None);
+			assert_eq!(KittyModule::name_deposit(0), 0);
+			assert_eq!(Balances::free_balance(&1), 120);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&2), 20);
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert!(System::events().iter().any(|record| record.event == TestEvent::kitties(RawEvent::NameCleared(2, 0))));
+		});
+	}
+
+	#[test]
+	fn sheltered_kitty_name_is_released() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 1, b"Jerry".to_vec()));
+
+			KittyModule::on_free_balance_zero(&1);
+
+			assert_eq!(KittyModule::kitty_name(0), None);
+			assert_eq!(KittyModule::kitty_name(1), None);
+			assert_eq!(KittyModule::named_kitty(b"Jerry".to_vec()), None);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::free_balance(&1), 100);
+		});
+	}
+
+	#[test]
+	fn create_fails_when_gen0_limit_reached() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).max_gen0_kitties(2).build(), || {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::create(Origin::signed(2)));
+			assert_eq!(KittyModule::gen0_count(), 2);
+
+			assert_noop!(KittyModule::create(Origin::signed(3)), "Gen-0 population limit reached");
+
+			// Breeding is not limited by the gen-0 population
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			run_to_block(3);
+			assert_eq!(KittyModule::gen0_count(), 2);
+			assert_eq!(KittyModule::kitties_count(), 4);
+		});
+	}
+
+	#[test]
+	fn account_limit_is_enforced_on_every_incoming_path() {
+		let builder = ExtBuilder::default()
+			.max_kitties_per_account(2)
+			.kitty(1).kitty(1)
+			.kitty(2).kitty(2)
+			.kitty(3);
+		with_externalities(&mut builder.build(), || {
+			assert_noop!(KittyModule::create(Origin::signed(1)), "Account has too many kitties");
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 1), "Account has too many kitties");
+			assert_noop!(KittyModule::transfer(Origin::signed(3), Address::Id(1), 4), "Account has too many kitties");
+			assert_noop!(KittyModule::force_transfer(Origin::ROOT, Address::Id(1), 4), "Account has too many kitties");
+
+			// The buyer is not charged
+			assert_ok!(KittyModule::ask(Origin::signed(3), 4, Some(10), None));
+			assert_noop!(KittyModule::buy(Origin::signed(1), 4, 10), "Account has too many kitties");
+
+			assert_ok!(KittyModule::set_transfer_approval(Origin::signed(1), true));
+			assert_ok!(KittyModule::transfer(Origin::signed(3), Address::Id(1), 4));
+			assert_noop!(KittyModule::accept_transfer(Origin::signed(1), 4), "Account has too many kitties");
+			assert_ok!(KittyModule::cancel_transfer(Origin::signed(3), 4));
+
+			KittyModule::on_free_balance_zero(&3);
+			assert_noop!(KittyModule::adopt(Origin::signed(1), 4), "Account has too many kitties");
+			assert_ok!(KittyModule::adopt(Origin::signed(3), 4));
+
+			assert_eq!(KittyModule::kitty_count_of(&1), 2);
+			assert_eq!(KittyModule::kitty_count_of(&2), 2);
+			assert_eq!(KittyModule::kitty_count_of(&3), 1);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn breeding_fee_rises_with_supply_and_generation() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			// 5 + 1 * 2 kitties
+			assert_eq!(KittyModule::breeding_fee(0, 1), 7);
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			run_to_block(3);
+
+			assert_eq!(KittyModule::kitty_generation(2), 1);
+			assert_eq!(Balances::free_balance(&1), 93);
+			assert_eq!(Balances::free_balance(&FEE_COLLECTOR), 7);
+
+			// 5 + 1 * 3 kitties + 10 * generation 1
+			assert_eq!(KittyModule::breeding_fee(0, 2), 18);
+			assert_eq!(KittyModule::breeding_fee_for(1), 18);
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 2));
+			run_to_block(5);
+
+			assert_eq!(KittyModule::kitty_generation(3), 2);
+			assert_eq!(Balances::free_balance(&1), 75);
+			assert_eq!(Balances::free_balance(&FEE_COLLECTOR), 25);
+
+			// Created kitties are generation 0
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_eq!(KittyModule::kitty_generation(4), 0);
+			assert_eq!(KittyModule::breeding_fee(3, 4), 5 + 5 + 20);
+		});
+	}
+
+	#[test]
+	fn breed_fails_when_fee_cannot_be_paid() {
+		with_externalities(&mut ExtBuilder::default().balances(vec![(1, 6)]).kitty(1).kitty(1).build(), || {
+			assert!(KittyModule::breed(Origin::signed(1), 0, 1).is_err());
+
+			assert_eq!(KittyModule::kitties_count(), 2);
+			assert_eq!(KittyModule::kitty_count_of(&1), 2);
+			assert_eq!(Balances::free_balance(&1), 6);
+			assert_eq!(Balances::free_balance(&FEE_COLLECTOR), 0);
+		});
+	}
+
+	#[test]
+	fn instances_do_not_share_storage() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(PuppyModule::create(Origin::signed(2)));
+			assert_ok!(PuppyModule::create(Origin::signed(2)));
+
+			// Both collections start at index 0
+			assert_eq!(KittyModule::kitties_count(), 1);
+			assert_eq!(PuppyModule::kitties_count(), 2u64);
+			assert_eq!(KittyModule::kitty_owner(0), Some(1));
+			assert_eq!(PuppyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::kitty_count_of(&2), 0);
+			assert_eq!(PuppyModule::kitty_count_of(&2), 2);
+
+			assert_ok!(PuppyModule::transfer(Origin::signed(2), Address::Id(3), 0));
+			assert_eq!(PuppyModule::kitty_owner(0), Some(3));
+			assert_eq!(KittyModule::kitty_owner(0), Some(1));
+			assert_noop!(KittyModule::transfer(Origin::signed(2), Address::Id(3), 1), "Only owner can transfer kitty");
+
+			// Names are unique within a collection only
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+			assert_ok!(PuppyModule::set_name(Origin::signed(3), 0, b"Tom".to_vec()));
+			assert_eq!(KittyModule::named_kitty(b"Tom".to_vec()), Some(0));
+			assert_eq!(PuppyModule::named_kitty(b"Tom".to_vec()), Some(0u64));
+
+			assert_ok!(PuppyModule::ask(Origin::signed(2), 1, Some(10), None));
+			assert_eq!(KittyModule::cheapest_kitty(), None);
+			assert_eq!(PuppyModule::cheapest_kitty(), Some((1, 10)));
+
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+			assert_eq!(PuppyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn instances_use_their_own_reap_lock() {
+		with_externalities(&mut ExtBuilder::default().build(), || {
+			let lock_ids = |who: u64| Balances::locks(&who).into_iter().map(|lock| lock.id).collect::<Vec<_>>();
+
+			assert_ok!(PuppyModule::create(Origin::signed(1)));
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_eq!(lock_ids(1), vec![*b"puppies "]);
+
+			// Reaping shelters the kitties but the puppies stay
+			KittyModule::on_free_balance_zero(&1);
+			assert_eq!(KittyModule::kitty_owner(0), None);
+			assert_eq!(PuppyModule::kitty_owner(0), Some(1));
+			assert_eq!(lock_ids(1), vec![*b"puppies "]);
+
+			assert_ok!(PuppyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+			assert_eq!(lock_ids(1), vec![]);
+			assert_eq!(lock_ids(2), vec![*b"puppies "]);
+		});
+	}
+
+	#[test]
+	fn kitties_implement_non_fungible_asset() {
+		type Nft = KittyModule;
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert!(<Nft as NonFungibleAsset<u64>>::exists(1));
+			assert!(!<Nft as NonFungibleAsset<u64>>::exists(2));
+			assert_eq!(<Nft as NonFungibleAsset<u64>>::owner_of(0), Some(1));
+			assert_eq!(<Nft as NonFungibleAsset<u64>>::owner_of(2), None);
+			assert_eq!(<Nft as NonFungibleAsset<u64>>::total_supply(), 2);
+			assert_eq!(<Nft as AssetDna<u64>>::dna(1), KittyModule::kitty(1).map(|kitty| kitty.0));
+			assert_eq!(<Nft as AssetDna<u64>>::dna(2), None);
+			assert!(<Nft as AssetDna<u64>>::can_play(1));
+			assert!(!<Nft as AssetDna<u64>>::can_play(2));
+
+			assert_noop!(<Nft as NonFungibleAsset<u64>>::transfer(&2, &3, 0), "Only owner can transfer kitty");
+			assert_ok!(<Nft as NonFungibleAsset<u64>>::transfer(&1, &2, 0));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Transferred(1, 2, 0)));
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn only_fit_kitties_can_play() {
+		type Nft = KittyModule;
+		with_externalities(&mut ExtBuilder::default().life_stages(10, 10, Some(30)).kitty(1).kitty(1).build(), || {
+			assert!(!<Nft as AssetDna<u64>>::can_play(0));
+
+			System::set_block_number(11);
+			assert!(<Nft as AssetDna<u64>>::can_play(0));
+			assert_ok!(KittyModule::freeze(Origin::ROOT, 1));
+			assert!(!<Nft as AssetDna<u64>>::can_play(1));
+			assert_ok!(<Nft as NonFungibleAsset<u64>>::lock(0));
+			assert!(!<Nft as AssetDna<u64>>::can_play(0));
+			assert_ok!(<Nft as NonFungibleAsset<u64>>::unlock(0));
+
+			System::set_block_number(21);
+			assert!(<Nft as AssetDna<u64>>::can_play(0));
+			System::set_block_number(31);
+			assert!(!<Nft as AssetDna<u64>>::can_play(0));
+		});
+	}
+
+	#[test]
+	fn locked_kitty_cannot_be_transferred_sold_or_bred() {
+		type Nft = KittyModule;
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+
+			assert_ok!(<Nft as NonFungibleAsset<u64>>::lock(0));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Locked(0)));
+			assert!(<Nft as NonFungibleAsset<u64>>::is_locked(0));
+			// Locking takes the kitty off the market
+			assert_eq!(KittyModule::kitty_price(0), None);
+			assert_noop!(<Nft as NonFungibleAsset<u64>>::lock(0), "Kitty is locked");
+			assert_noop!(<Nft as NonFungibleAsset<u64>>::lock(1), "Kitty does not exist");
+
+			assert_noop!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0), "Kitty is locked");
+			assert_noop!(KittyModule::ask(Origin::signed(1), 0, Some(10), None), "Kitty is locked");
+			assert_noop!(KittyModule::force_transfer(Origin::ROOT, Address::Id(2), 0), "Kitty is locked");
+			assert_noop!(<Nft as NonFungibleAsset<u64>>::transfer(&1, &2, 0), "Kitty is locked");
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 1), "Kitty is locked");
+
+			assert_ok!(<Nft as NonFungibleAsset<u64>>::unlock(0));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Unlocked(0)));
+			assert_noop!(<Nft as NonFungibleAsset<u64>>::unlock(0), "Kitty is not locked");
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+		});
+	}
+
+	#[test]
+	fn co_owned_kitty_is_in_every_co_owner_list() {
+		type Nft = KittyModule;
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::ask(Origin::signed(1), 0, Some(10), None));
+
+			assert_noop!(KittyModule::co_own(Origin::signed(2), 0, vec![(1, 1), (2, 1)], 1), "Only owner can share kitty");
+			assert_noop!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1)], 1), "Invalid number of co-owners");
+			assert_noop!(KittyModule::co_own(Origin::signed(1), 0, vec![(2, 1), (3, 1)], 1), "Owner must be a co-owner");
+			assert_noop!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (2, 0)], 1), "Share is zero");
+			assert_noop!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (2, 1)], 3), "Invalid threshold");
+			assert_noop!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (1, 1)], 1), "Duplicate co-owner");
+
+			assert_ok!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (2, 1)], 2));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::CoOwned(1, 0, 2)));
+			assert_eq!(KittyModule::kitties_of(&1, None, 10), vec![0]);
+			assert_eq!(KittyModule::kitties_of(&2, None, 10), vec![0]);
+			// Sharing takes the kitty off the market
+			assert_eq!(KittyModule::kitty_price(0), None);
+			assert_eq!(<Nft as NonFungibleAsset<u64>>::owner_of(0), None);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+
+			assert_noop!(KittyModule::transfer(Origin::signed(1), Address::Id(3), 0), "Kitty is co-owned");
+			assert_noop!(KittyModule::ask(Origin::signed(1), 0, Some(10), None), "Kitty is co-owned");
+			assert_noop!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()), "Kitty is co-owned");
+			assert_noop!(<Nft as NonFungibleAsset<u64>>::transfer(&1, &3, 0), "Kitty is co-owned");
+			assert_noop!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (3, 1)], 1), "Kitty is co-owned");
+		});
+	}
+
+	#[test]
+	fn root_can_force_transfer_co_owned_kitty() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (2, 1)], 2));
+			assert_ok!(KittyModule::propose_co_owned(Origin::signed(2), 0, CoOwnedAction::Ask(Some(10), None)));
+
+			assert_ok!(KittyModule::force_transfer(Origin::ROOT, Address::Id(3), 0));
+
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Transferred(1, 3, 0)));
+			assert_eq!(KittyModule::kitty_owner(0), Some(3));
+			assert_eq!(KittyModule::co_owners(0), None);
+			assert_eq!(KittyModule::co_owner_proposal(0), None);
+			assert_eq!(KittyModule::kitty_count_of(&1), 0);
+			assert_eq!(KittyModule::kitty_count_of(&2), 0);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+
+			// Locked kitties stay with the module holding the lock
+			assert_ok!(<KittyModule as NonFungibleAsset<u64>>::lock(0));
+			assert_noop!(KittyModule::force_transfer(Origin::ROOT, Address::Id(1), 0), "Kitty is locked");
+		});
+	}
+
+	#[test]
+	fn co_owners_transfer_once_threshold_is_reached() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (2, 1), (3, 1)], 2));
+
+			assert_noop!(KittyModule::approve_co_owned(Origin::signed(2), 0), "No open proposal");
+			assert_noop!(
+				KittyModule::propose_co_owned(Origin::signed(4), 0, CoOwnedAction::Transfer(4)),
+				"Only co-owner can propose"
+			);
+
+			assert_ok!(KittyModule::propose_co_owned(Origin::signed(1), 0, CoOwnedAction::Transfer(4)));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::CoOwnerProposed(1, 0)));
+			assert_eq!(KittyModule::kitty_owner(0), Some(1));
+			assert_noop!(KittyModule::approve_co_owned(Origin::signed(1), 0), "Already approved");
+			assert_noop!(KittyModule::approve_co_owned(Origin::signed(4), 0), "Only co-owner can approve");
+
+			assert_ok!(KittyModule::approve_co_owned(Origin::signed(3), 0));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::CoOwnerProposalExecuted(0)));
+			assert_eq!(KittyModule::kitty_owner(0), Some(4));
+			assert_eq!(KittyModule::co_owners(0), None);
+			assert_eq!(KittyModule::co_owner_proposal(0), None);
+			assert_eq!(KittyModule::kitty_count_of(&1), 0);
+			assert_eq!(KittyModule::kitty_count_of(&2), 0);
+			assert_eq!(KittyModule::kitty_count_of(&3), 0);
+			assert_eq!(KittyModule::kitties_of(&4, None, 10), vec![0]);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn co_owned_sale_splits_proceeds_by_share() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 3), (2, 1)], 2));
+
+			assert_ok!(KittyModule::propose_co_owned(Origin::signed(2), 0, CoOwnedAction::Ask(Some(10), None)));
+			assert_noop!(KittyModule::buy(Origin::signed(3), 0, 10), "Kitty not for sale");
+			assert_ok!(KittyModule::approve_co_owned(Origin::signed(1), 0));
+			assert_eq!(KittyModule::kitty_price(0), Some(10));
+
+			assert_noop!(KittyModule::buy(Origin::signed(2), 0, 10), "Cannot buy your own kitty");
+			assert_ok!(KittyModule::buy(Origin::signed(3), 0, 10));
+			assert_eq!(last_event(), TestEvent::kitties(RawEvent::Sold(1, 3, 0, 10)));
+
+			// The first co-owner gets the rounding of the 2.5 of the second
+			assert_eq!(Balances::free_balance(&1), 108);
+			assert_eq!(Balances::free_balance(&2), 102);
+			assert_eq!(Balances::free_balance(&3), 90);
+			assert_eq!(KittyModule::kitty_owner(0), Some(3));
+			assert_eq!(KittyModule::co_owners(0), None);
+			assert_eq!(KittyModule::kitty_count_of(&1), 0);
+			assert_eq!(KittyModule::kitty_count_of(&2), 0);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn co_owners_breed_their_kitties() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(1).build(), || {
+			assert_ok!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (2, 1)], 2));
+			assert_ok!(KittyModule::co_own(Origin::signed(1), 1, vec![(1, 1), (2, 1)], 2));
+
+			assert_noop!(KittyModule::breed(Origin::signed(1), 0, 1), "Kitty is co-owned");
+
+			assert_ok!(KittyModule::propose_co_owned(Origin::signed(2), 0, CoOwnedAction::Breed(2)));
+			assert_noop!(KittyModule::approve_co_owned(Origin::signed(1), 0), "Partner has other co-owners");
+
+			assert_ok!(KittyModule::propose_co_owned(Origin::signed(2), 0, CoOwnedAction::Breed(1)));
+			assert_ok!(KittyModule::approve_co_owned(Origin::signed(1), 0));
+			// The owner in `KittyOwners` pays the fee and gets the egg
+			assert_eq!(KittyModule::eggs_of(&1), vec![0]);
+			assert_eq!(Balances::free_balance(&1), 100 - KittyModule::breeding_fee_for(0));
+			assert_eq!(Balances::free_balance(&2), 100);
+
+			run_to_block(3);
+			assert_eq!(KittyModule::kitty_owner(3), Some(1));
+			assert_eq!(KittyModule::co_owners(3), None);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn co_owners_cannot_clear_the_name() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+			assert_ok!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (2, 1)], 2));
+			let deposit = KittyModule::name_deposit(0);
+			assert_ok!(Balances::reserve(&2, deposit));
+
+			assert_noop!(KittyModule::clear_name(Origin::signed(2), 0), "Kitty is co-owned");
+			assert_noop!(KittyModule::clear_name(Origin::signed(1), 0), "Kitty is co-owned");
+			assert_eq!(Balances::reserved_balance(&1), deposit);
+			assert_eq!(Balances::reserved_balance(&2), deposit);
+
+			// The deposit goes back to the account that named the kitty when it leaves
+			KittyModule::on_free_balance_zero(&1);
+			assert_eq!(KittyModule::kitty_name(0), None);
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::reserved_balance(&2), deposit);
+		});
+	}
+
+	#[test]
+	fn reaped_co_owner_leaves_the_co_ownership() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+			assert_ok!(KittyModule::co_own(Origin::signed(1), 0, vec![(1, 1), (2, 1), (3, 1)], 3));
+			assert_ok!(KittyModule::propose_co_owned(Origin::signed(2), 0, CoOwnedAction::Ask(Some(10), None)));
+
+			KittyModule::on_free_balance_zero(&1);
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::co_owners(0), Some(CoOwnership { owners: vec![(2, 1), (3, 1)], threshold: 2 }));
+			assert_eq!(KittyModule::co_owner_proposal(0), None);
+			assert_eq!(KittyModule::kitty_count_of(&1), 0);
+			assert_eq!(KittyModule::check_integrity(), vec![]);
+
+			// The last one left owns the kitty alone
+			KittyModule::on_free_balance_zero(&3);
+			assert_eq!(KittyModule::kitty_owner(0), Some(2));
+			assert_eq!(KittyModule::co_owners(0), None);
+			assert_ok!(KittyModule::transfer(Origin::signed(2), Address::Id(3), 0));
+		});
+	}
+
+	#[test]
+	fn hooks_are_called_when_kitties_are_created_and_transferred() {
+		with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+			assert_eq!(CREATED.with(|v| v.borrow().clone()), vec![(Some(1), 0), (Some(1), 1)]);
+
+			assert_ok!(KittyModule::create(Origin::signed(2)));
+			assert_ok!(KittyModule::breed(Origin::signed(1), 0, 1));
+			// Bred kitties are created when they hatch
+			assert_eq!(CREATED.with(|v| v.borrow().len()), 3);
+			run_to_block(3);
+			assert_eq!(CREATED.with(|v| v.borrow().clone()), vec![(Some(1), 0), (Some(1), 1), (Some(2), 2), (Some(1), 3)]);
+
+			assert_ok!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0));
+			assert_ok!(KittyModule::ask(Origin::signed(2), 0, Some(10), None));
+			assert_ok!(KittyModule::buy(Origin::signed(3), 0, 10));
+			assert_noop!(KittyModule::transfer(Origin::signed(1), Address::Id(2), 0), "Only owner can transfer kitty");
+			assert_eq!(TRANSFERRED.with(|v| v.borrow().clone()), vec![(Some(1), 2, 0, None), (Some(2), 3, 0, Some(10))]);
+
+			// So are new co-owners and adopters
+			TRANSFERRED.with(|v| v.borrow_mut().clear());
+			assert_ok!(KittyModule::co_own(Origin::signed(3), 0, vec![(3, 1), (1, 1)], 1));
+			KittyModule::on_free_balance_zero(&2);
+			assert_ok!(KittyModule::adopt(Origin::signed(3), 2));
+			assert_eq!(TRANSFERRED.with(|v| v.borrow().clone()), vec![(Some(3), 1, 0, None), (None, 3, 2, None)]);
+
+			// Eggs of reaped accounts hatch into the shelter without an owner
+			assert_ok!(KittyModule::breed(Origin::signed(1), 1, 3));
+			KittyModule::on_free_balance_zero(&1);
+			assert_eq!(CREATED.with(|v| v.borrow().last().cloned()), Some((None, 4)));
+
+			// Only the subscribed instance calls them
+			assert_ok!(PuppyModule::create(Origin::signed(1)));
+			assert_eq!(CREATED.with(|v| v.borrow().len()), 5);
+		});
+	}
 }
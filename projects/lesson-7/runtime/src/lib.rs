@@ -34,6 +34,7 @@ pub use timestamp::Call as TimestampCall;
 pub use balances::Call as BalancesCall;
 pub use sr_primitives::{Permill, Perbill};
 pub use support::{StorageValue, construct_runtime, parameter_types};
+use support::traits::LockIdentifier;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -61,13 +62,37 @@ pub type Hash = primitives::H256;
 /// Digest item type.
 pub type DigestItem = generic::DigestItem<Hash>;
 
+/// Index of a kitty.
+pub type KittyIndex = u32;
+
+/// Index of a puppy, the second collection of the kitties module.
+pub type PuppyIndex = u64;
+
 /// Used for the module template in `./template.rs`
 mod template;
 
 /// Used for the module kitties in `./kitties.rs`
-mod kitties;
+pub mod kitties;
+
+pub mod linked_item;
+
+/// Non-fungible asset trait in `./nft.rs`, implemented by the kitties module
+pub mod nft;
+
+/// Loans against kitties in `./loans.rs`
+pub mod loans;
 
-mod linked_item;
+/// Fractional ownership of kitties in `./fractional.rs`
+pub mod fractional;
+
+/// Kitty battles and tournaments in `./battle.rs`
+pub mod battle;
+
+/// Achievements in `./achievements.rs`, unlocked through the kitties hooks
+pub mod achievements;
+
+/// Runtime APIs of the kitties module in `./api.rs`
+pub mod api;
 
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
@@ -102,7 +127,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("substrate-kitties"),
 	impl_name: create_runtime_str!("substrate-kitties"),
 	authoring_version: 3,
-	spec_version: 4,
+	spec_version: 5,
 	impl_version: 4,
 	apis: RUNTIME_API_VERSIONS,
 };
@@ -235,7 +260,7 @@ impl balances::Trait for Runtime {
 	/// The type for recording an account's balance.
 	type Balance = Balance;
 	/// What to do if an account's free balance gets zeroed.
-	type OnFreeBalanceZero = ();
+	type OnFreeBalanceZero = (Kitties, Puppies);
 	/// What to do if a new account is created.
 	type OnNewAccount = Indices;
 	/// The ubiquitous event type.
@@ -261,10 +286,104 @@ impl template::Trait for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const KittyReapPolicy: kitties::ReapPolicy = kitties::ReapPolicy::BlockReaping;
+	pub const KittiesLockId: LockIdentifier = *b"kitties ";
+	pub const PuppiesLockId: LockIdentifier = *b"puppies ";
+	pub const NameByteDeposit: u128 = 100;
+	pub const MaxNameLength: u32 = 32;
+	pub const MaxKittiesPerAccount: u32 = 1_000;
+	pub const MaxGen0Kitties: u32 = 10_000;
+	pub const BreedingBaseFee: u128 = 1_000;
+	pub const BreedingFeePerKitty: u128 = 10;
+	pub const BreedingFeePerGeneration: u128 = 500;
+	pub const HatchingPeriod: BlockNumber = HOURS;
+	pub const KittenPeriod: BlockNumber = DAYS;
+	pub const AdultPeriod: BlockNumber = 60 * DAYS;
+	pub const Lifespan: Option<BlockNumber> = Some(365 * DAYS);
+}
+
 impl kitties::Trait for Runtime {
 	type Event = Event;
-	type KittyIndex = u32;
+	type KittyIndex = KittyIndex;
+	type Currency = Balances;
+	type KittyReapPolicy = KittyReapPolicy;
+	type ReapLockId = KittiesLockId;
+	type NameByteDeposit = NameByteDeposit;
+	type MaxNameLength = MaxNameLength;
+	type MaxKittiesPerAccount = MaxKittiesPerAccount;
+	type MaxGen0Kitties = MaxGen0Kitties;
+	type BreedingBaseFee = BreedingBaseFee;
+	type BreedingFeePerKitty = BreedingFeePerKitty;
+	type BreedingFeePerGeneration = BreedingFeePerGeneration;
+	/// Breeding fees are burned
+	type BreedingFeeDestination = ();
+	type HatchingPeriod = HatchingPeriod;
+	type KittenPeriod = KittenPeriod;
+	type AdultPeriod = AdultPeriod;
+	type Lifespan = Lifespan;
+	type OnKittyCreated = Achievements;
+	type OnKittyTransferred = Achievements;
+}
+
+impl kitties::Trait<kitties::Instance1> for Runtime {
+	type Event = Event;
+	type KittyIndex = PuppyIndex;
+	type Currency = Balances;
+	type KittyReapPolicy = KittyReapPolicy;
+	type ReapLockId = PuppiesLockId;
+	type NameByteDeposit = NameByteDeposit;
+	type MaxNameLength = MaxNameLength;
+	type MaxKittiesPerAccount = MaxKittiesPerAccount;
+	type MaxGen0Kitties = MaxGen0Kitties;
+	type BreedingBaseFee = BreedingBaseFee;
+	type BreedingFeePerKitty = BreedingFeePerKitty;
+	type BreedingFeePerGeneration = BreedingFeePerGeneration;
+	type BreedingFeeDestination = ();
+	type HatchingPeriod = HatchingPeriod;
+	type KittenPeriod = KittenPeriod;
+	type AdultPeriod = AdultPeriod;
+	type Lifespan = Lifespan;
+	type OnKittyCreated = ();
+	type OnKittyTransferred = ();
+}
+
+impl loans::Trait for Runtime {
+	type Event = Event;
 	type Currency = Balances;
+	type Nft = Kitties;
+}
+
+parameter_types! {
+	pub const SharesPerVault: fractional::Shares = 1_000_000;
+	pub const BuyoutPeriod: BlockNumber = 7 * DAYS;
+}
+
+impl fractional::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Nft = Kitties;
+	type SharesPerVault = SharesPerVault;
+	type BuyoutPeriod = BuyoutPeriod;
+}
+
+parameter_types! {
+	pub const MaxTournamentEntrants: u32 = 64;
+	pub const TournamentDeposit: u128 = 10_000;
+	pub const MaxTournamentsPerBlock: u32 = 4;
+}
+
+impl battle::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Nft = Kitties;
+	type MaxTournamentEntrants = MaxTournamentEntrants;
+	type TournamentDeposit = TournamentDeposit;
+	type MaxTournamentsPerBlock = MaxTournamentsPerBlock;
+}
+
+impl achievements::Trait for Runtime {
+	type Event = Event;
 }
 
 construct_runtime!(
@@ -284,6 +403,11 @@ construct_runtime!(
 		TemplateModule: template::{Module, Call, Storage, Event<T>},
 		// Substrate Kitties module
 		Kitties: kitties::{Module, Storage, Call, Event<T>},
+		Puppies: kitties::<Instance1>::{Module, Storage, Call, Event<T>},
+		Loans: loans::{Module, Storage, Call, Event<T>},
+		Fractional: fractional::{Module, Storage, Call, Event<T>},
+		Battle: battle::{Module, Storage, Call, Event<T>},
+		Achievements: achievements::{Module, Storage, Event<T>},
 	}
 );
 
@@ -313,109 +437,163 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExt
 /// Executive: handles dispatch to the various modules.
 pub type Executive = executive::Executive<Runtime, Block, system::ChainContext<Runtime>, Runtime, AllModules>;
 
-impl_runtime_apis! {
-	impl client_api::Core<Block> for Runtime {
-		fn version() -> RuntimeVersion {
-			VERSION
-		}
+/// Implements the runtime APIs, together with the `extra` implementations given
+macro_rules! impl_kitties_runtime_apis {
+	( $( $extra:tt )* ) => {
+		impl_runtime_apis! {
+			$( $extra )*
 
-		fn execute_block(block: Block) {
-			Executive::execute_block(block)
-		}
+			impl client_api::Core<Block> for Runtime {
+				fn version() -> RuntimeVersion {
+					VERSION
+				}
 
-		fn initialize_block(header: &<Block as BlockT>::Header) {
-			Executive::initialize_block(header)
-		}
-	}
+				fn execute_block(block: Block) {
+					Executive::execute_block(block)
+				}
 
-	impl client_api::Metadata<Block> for Runtime {
-		fn metadata() -> OpaqueMetadata {
-			Runtime::metadata().into()
-		}
-	}
+				fn initialize_block(header: &<Block as BlockT>::Header) {
+					Executive::initialize_block(header)
+				}
+			}
 
-	impl block_builder_api::BlockBuilder<Block> for Runtime {
-		fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyResult {
-			Executive::apply_extrinsic(extrinsic)
-		}
+			impl client_api::Metadata<Block> for Runtime {
+				fn metadata() -> OpaqueMetadata {
+					Runtime::metadata().into()
+				}
+			}
 
-		fn finalize_block() -> <Block as BlockT>::Header {
-			Executive::finalize_block()
-		}
+			impl block_builder_api::BlockBuilder<Block> for Runtime {
+				fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyResult {
+					Executive::apply_extrinsic(extrinsic)
+				}
 
-		fn inherent_extrinsics(data: InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
-			data.create_extrinsics()
-		}
+				fn finalize_block() -> <Block as BlockT>::Header {
+					Executive::finalize_block()
+				}
 
-		fn check_inherents(block: Block, data: InherentData) -> CheckInherentsResult {
-			data.check_extrinsics(&block)
-		}
+				fn inherent_extrinsics(data: InherentData) -> Vec<<Block as BlockT>::Extrinsic> {
+					data.create_extrinsics()
+				}
 
-		fn random_seed() -> <Block as BlockT>::Hash {
-			System::random_seed()
-		}
-	}
+				fn check_inherents(block: Block, data: InherentData) -> CheckInherentsResult {
+					data.check_extrinsics(&block)
+				}
 
-	impl client_api::TaggedTransactionQueue<Block> for Runtime {
-		fn validate_transaction(tx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
-			Executive::validate_transaction(tx)
-		}
-	}
+				fn random_seed() -> <Block as BlockT>::Hash {
+					System::random_seed()
+				}
+			}
 
-	impl offchain_primitives::OffchainWorkerApi<Block> for Runtime {
-		fn offchain_worker(number: NumberFor<Block>) {
-			Executive::offchain_worker(number)
-		}
-	}
+			impl client_api::TaggedTransactionQueue<Block> for Runtime {
+				fn validate_transaction(tx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
+					Executive::validate_transaction(tx)
+				}
+			}
 
-	impl fg_primitives::GrandpaApi<Block> for Runtime {
-		fn grandpa_pending_change(digest: &DigestFor<Block>)
-			-> Option<ScheduledChange<NumberFor<Block>>>
-		{
-			Grandpa::pending_change(digest)
-		}
+			impl offchain_primitives::OffchainWorkerApi<Block> for Runtime {
+				fn offchain_worker(number: NumberFor<Block>) {
+					Executive::offchain_worker(number)
+				}
+			}
 
-		fn grandpa_forced_change(digest: &DigestFor<Block>)
-			-> Option<(NumberFor<Block>, ScheduledChange<NumberFor<Block>>)>
-		{
-			Grandpa::forced_change(digest)
-		}
+			impl fg_primitives::GrandpaApi<Block> for Runtime {
+				fn grandpa_pending_change(digest: &DigestFor<Block>)
+					-> Option<ScheduledChange<NumberFor<Block>>>
+				{
+					Grandpa::pending_change(digest)
+				}
+
+				fn grandpa_forced_change(digest: &DigestFor<Block>)
+					-> Option<(NumberFor<Block>, ScheduledChange<NumberFor<Block>>)>
+				{
+					Grandpa::forced_change(digest)
+				}
+
+				fn grandpa_authorities() -> Vec<(GrandpaId, GrandpaWeight)> {
+					Grandpa::grandpa_authorities()
+				}
+			}
 
-		fn grandpa_authorities() -> Vec<(GrandpaId, GrandpaWeight)> {
-			Grandpa::grandpa_authorities()
-		}
-	}
+			impl babe_primitives::BabeApi<Block> for Runtime {
+				fn startup_data() -> babe_primitives::BabeConfiguration {
+					// The choice of `c` parameter (where `1 - c` represents the
+					// probability of a slot being empty), is done in accordance to the
+					// slot duration and expected target block time, for safely
+					// resisting network delays of maximum two seconds.
+					// <https://research.web3.foundation/en/latest/polkadot/BABE/Babe/#6-practical-results>
+					babe_primitives::BabeConfiguration {
+						median_required_blocks: 1000,
+						slot_duration: Babe::slot_duration(),
+						c: PRIMARY_PROBABILITY,
+					}
+				}
+
+				fn epoch() -> babe_primitives::Epoch {
+					babe_primitives::Epoch {
+						start_slot: Babe::epoch_start_slot(),
+						authorities: Babe::authorities(),
+						epoch_index: Babe::epoch_index(),
+						randomness: Babe::randomness(),
+						duration: EpochDuration::get(),
+						secondary_slots: Babe::secondary_slots().0,
+					}
+				}
+			}
+
+			impl api::KittiesApi<Block, AccountId, KittyIndex, Balance> for Runtime {
+				fn kitty_count_of(owner: AccountId) -> u32 {
+					Kitties::kitty_count_of(&owner)
+				}
+
+				fn kitties_of(owner: AccountId, start_after: Option<KittyIndex>, limit: u32) -> Vec<KittyIndex> {
+					Kitties::kitties_of(&owner, start_after, limit)
+				}
 
-	impl babe_primitives::BabeApi<Block> for Runtime {
-		fn startup_data() -> babe_primitives::BabeConfiguration {
-			// The choice of `c` parameter (where `1 - c` represents the
-			// probability of a slot being empty), is done in accordance to the
-			// slot duration and expected target block time, for safely
-			// resisting network delays of maximum two seconds.
-			// <https://research.web3.foundation/en/latest/polkadot/BABE/Babe/#6-practical-results>
-			babe_primitives::BabeConfiguration {
-				median_required_blocks: 1000,
-				slot_duration: Babe::slot_duration(),
-				c: PRIMARY_PROBABILITY,
+				fn listings(start_after: Option<KittyIndex>, limit: u32) -> Vec<(KittyIndex, Balance)> {
+					Kitties::listings_page(start_after, limit)
+				}
+
+				fn breeding_fee(kitty_id_1: KittyIndex, kitty_id_2: KittyIndex) -> Balance {
+					Kitties::breeding_fee(kitty_id_1, kitty_id_2)
+				}
+
+				fn breeding_fee_for(generation: u32) -> Balance {
+					Kitties::breeding_fee_for(generation)
+				}
+
+				fn eggs_of(owner: AccountId) -> Vec<kitties::EggId> {
+					Kitties::eggs_of(&owner)
+				}
+
+				fn life_stage(kitty_id: KittyIndex) -> Option<kitties::LifeStage> {
+					Kitties::life_stage(kitty_id)
+				}
+
+				fn appearance(kitty_id: KittyIndex) -> Option<kitties::Appearance> {
+					Kitties::appearance(kitty_id)
+				}
 			}
-		}
 
-		fn epoch() -> babe_primitives::Epoch {
-			babe_primitives::Epoch {
-				start_slot: Babe::epoch_start_slot(),
-				authorities: Babe::authorities(),
-				epoch_index: Babe::epoch_index(),
-				randomness: Babe::randomness(),
-				duration: EpochDuration::get(),
-				secondary_slots: Babe::secondary_slots().0,
+			impl substrate_session::SessionKeys<Block> for Runtime {
+				fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
+					let seed = seed.as_ref().map(|s| rstd::str::from_utf8(&s).expect("Seed is an utf8 string"));
+					opaque::SessionKeys::generate(seed)
+				}
 			}
 		}
-	}
+	};
+}
 
-	impl substrate_session::SessionKeys<Block> for Runtime {
-		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
-			let seed = seed.as_ref().map(|s| rstd::str::from_utf8(&s).expect("Seed is an utf8 string"));
-			opaque::SessionKeys::generate(seed)
+// The debug API walks the whole kitties storage, so release runtimes leave it out
+#[cfg(feature = "debug-api")]
+impl_kitties_runtime_apis! {
+	impl api::KittiesDebugApi<Block, AccountId, KittyIndex> for Runtime {
+		fn check_integrity() -> Vec<kitties::IntegrityError<AccountId, KittyIndex>> {
+			Kitties::check_integrity()
 		}
 	}
 }
+
+#[cfg(not(feature = "debug-api"))]
+impl_kitties_runtime_apis! {}
@@ -1,80 +1,63 @@
 use support::{StorageMap, Parameter};
-use sr_primitives::traits::Member;
-use codec::{Encode, Decode};
+use rstd::marker::PhantomData;
+use linked_list::{ListStorage, SortKeyStorage};
 
-#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
-#[derive(Encode, Decode)]
-pub struct LinkedItem<Value> {
-	pub prev: Option<Value>,
-	pub next: Option<Value>,
-}
+pub use linked_list::{LinkedItem, LinkError, Iter, Compare, Ascending};
 
-pub struct LinkedList<Storage, Key, Value>(rstd::marker::PhantomData<(Storage, Key, Value)>);
+/// Keeps the items of the lists in a `StorageMap` keyed by `(Key, Option<Value>)`
+/// and the number of values of each list in the `Lens` map
+pub struct MapStorage<Storage, Lens>(PhantomData<(Storage, Lens)>);
 
-impl<Storage, Key, Value> LinkedList<Storage, Key, Value> where
-    Value: Parameter + Member + Copy,
-    Key: Parameter,
-    Storage: StorageMap<(Key, Option<Value>), LinkedItem<Value>, Query = Option<LinkedItem<Value>>>,
+impl<Storage, Lens, Key, Value> ListStorage<Key, Value> for MapStorage<Storage, Lens> where
+	Key: Parameter,
+	Value: Parameter,
+	Storage: StorageMap<(Key, Option<Value>), LinkedItem<Value>, Query = Option<LinkedItem<Value>>>,
+	Lens: StorageMap<Key, u32, Query = Option<u32>>,
 {
-    fn read_head(key: &Key) -> LinkedItem<Value> {
-		Self::read(key, None)
+	fn get(key: &Key, value: Option<Value>) -> Option<LinkedItem<Value>> {
+		Storage::get(&(key.clone(), value))
 	}
 
-	fn write_head(account: &Key, item: LinkedItem<Value>) {
-		Self::write(account, None, item);
+	fn insert(key: &Key, value: Option<Value>, item: LinkedItem<Value>) {
+		Storage::insert(&(key.clone(), value), item)
 	}
 
-	fn read(key: &Key, value: Option<Value>) -> LinkedItem<Value> {
-		Storage::get(&(key.clone(), value)).unwrap_or_else(|| LinkedItem {
-			prev: None,
-			next: None,
-		})
+	fn take(key: &Key, value: Option<Value>) -> Option<LinkedItem<Value>> {
+		Storage::take(&(key.clone(), value))
 	}
 
-	fn write(key: &Key, value: Option<Value>, item: LinkedItem<Value>) {
-		Storage::insert(&(key.clone(), value), item);
+	fn exists(key: &Key, value: Option<Value>) -> bool {
+		Storage::exists(&(key.clone(), value))
 	}
 
-	pub fn append(key: &Key, value: Value) {
-		let head = Self::read_head(key);
-		let new_head = LinkedItem {
-			prev: Some(value),
-			next: head.next,
-		};
-
-		Self::write_head(key, new_head);
-
-		let prev = Self::read(key, head.prev);
-		let new_prev = LinkedItem {
-			prev: prev.prev,
-			next: Some(value),
-		};
-		Self::write(key, head.prev, new_prev);
+	fn len(key: &Key) -> Option<u32> {
+		Lens::get(key)
+	}
 
-		let item = LinkedItem {
-			prev: head.prev,
-			next: None,
-		};
-		Self::write(key, Some(value), item);
+	fn set_len(key: &Key, len: u32) {
+		match len {
+			0 => Lens::remove(key),
+			len => Lens::insert(key, len),
+		}
 	}
+}
 
-	pub fn remove(key: &Key, value: Value) {
-		if let Some(item) = Storage::take(&(key.clone(), Some(value))) {
-			let prev = Self::read(key, item.prev);
-			let new_prev = LinkedItem {
-				prev: prev.prev,
-				next: item.next,
-			};
+/// Reads the sort keys of a `SortedLinkedList` from a `StorageMap`
+pub struct MapSortKeys<SortKeys>(PhantomData<SortKeys>);
 
-			Self::write(key, item.prev, new_prev);
+impl<SortKeys, Value, SortKey> SortKeyStorage<Value, SortKey> for MapSortKeys<SortKeys> where
+	Value: Parameter,
+	SortKey: Parameter,
+	SortKeys: StorageMap<Value, SortKey, Query = Option<SortKey>>,
+{
+	fn get(value: &Value) -> Option<SortKey> {
+		SortKeys::get(value)
+	}
+}
 
-			let next = Self::read(key, item.next);
-			let new_next = LinkedItem {
-				prev: item.prev,
-				next: next.next,
-			};
+/// A `linked_list::LinkedList` kept in the `Storage` and `Lens` maps
+pub type LinkedList<Storage, Lens, Key, Value> = linked_list::LinkedList<MapStorage<Storage, Lens>, Key, Value>;
 
-			Self::write(key, item.next, new_next);
-		}
-	}
-}
\ No newline at end of file
+/// A `linked_list::SortedLinkedList` kept in the `Storage` and `Lens` maps, sorted by the `SortKeys` map
+pub type SortedLinkedList<Storage, Lens, SortKeys, Key, Value, SortKey, Comparator> =
+	linked_list::SortedLinkedList<MapStorage<Storage, Lens>, MapSortKeys<SortKeys>, Key, Value, SortKey, Comparator>;
@@ -0,0 +1,485 @@
+use support::{
+	decl_module, decl_storage, decl_event, ensure, StorageValue, StorageMap,
+	traits::{Currency, ExistenceRequirement, WithdrawReason},
+};
+use sr_primitives::traits::{Zero, Saturating};
+use codec::{Encode, Decode};
+use system::ensure_signed;
+use rstd::result;
+use crate::nft::NonFungibleAsset;
+
+pub trait Trait: system::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	type Currency: Currency<Self::AccountId>;
+	/// The assets taken as collateral
+	type Nft: NonFungibleAsset<Self::AccountId>;
+}
+
+pub type LoanId = u64;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type AssetIdOf<T> = <<T as Trait>::Nft as NonFungibleAsset<<T as system::Trait>::AccountId>>::AssetId;
+type LoanOf<T> = Loan<
+	<T as system::Trait>::AccountId,
+	AssetIdOf<T>,
+	BalanceOf<T>,
+	<T as system::Trait>::BlockNumber,
+>;
+
+/// A loan secured by an asset of the borrower
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode, Clone)]
+pub struct Loan<AccountId, AssetId, Balance, BlockNumber> {
+	pub borrower: AccountId,
+	/// Locked until the loan is repaid, cancelled or claimed
+	pub collateral: AssetId,
+	/// Paid by the lender to the borrower when the loan is funded
+	pub principal: Balance,
+	/// Paid by the borrower to the lender to get the collateral back
+	pub repayment: Balance,
+	/// Number of blocks the borrower has to repay once the loan is funded
+	pub duration: BlockNumber,
+	/// The lender and the last block to repay, once the loan is funded
+	pub funding: Option<(AccountId, BlockNumber)>,
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Loans {
+		/// Get loan. Requested loans stay here until they are cancelled, repaid or claimed.
+		pub Loans get(loan): map LoanId => Option<LoanOf<T>>;
+		/// The id of the next loan
+		pub NextLoanId get(next_loan_id): LoanId;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::BlockNumber,
+		AssetId = AssetIdOf<T>,
+		Balance = BalanceOf<T>,
+	{
+		/// A loan is requested against a collateral. (loan_id, borrower, collateral, principal, repayment)
+		Requested(LoanId, AccountId, AssetId, Balance, Balance),
+		/// A loan request is cancelled by the borrower. (loan_id)
+		Cancelled(LoanId),
+		/// A loan is funded. (loan_id, lender, deadline)
+		Funded(LoanId, AccountId, BlockNumber),
+		/// A loan is repaid and the collateral returned. (loan_id)
+		Repaid(LoanId),
+		/// The collateral of a defaulted loan is claimed by the lender. (loan_id, lender)
+		Claimed(LoanId, AccountId),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		fn deposit_event() = default;
+
+		/// Request a loan of `principal`, locking `collateral` until the loan is settled.
+		/// The borrower has `duration` blocks after funding to pay back `repayment`.
+		pub fn request_loan(origin, collateral: AssetIdOf<T>, principal: BalanceOf<T>, repayment: BalanceOf<T>, duration: T::BlockNumber) {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(T::Nft::owner_of(collateral) == Some(sender.clone()), "Only owner can borrow against asset");
+			ensure!(!principal.is_zero(), "Principal is zero");
+			ensure!(repayment >= principal, "Repayment is below principal");
+			ensure!(!duration.is_zero(), "Duration is zero");
+
+			let loan_id = Self::next_loan_id();
+			let next_loan_id = loan_id.checked_add(1).ok_or("Loans count overflow")?;
+
+			T::Nft::lock(collateral)?;
+
+			<Loans<T>>::insert(loan_id, Loan {
+				borrower: sender.clone(),
+				collateral,
+				principal,
+				repayment,
+				duration,
+				funding: None,
+			});
+			NextLoanId::put(next_loan_id);
+
+			Self::deposit_event(RawEvent::Requested(loan_id, sender, collateral, principal, repayment));
+		}
+
+		/// Cancel a loan request that is not funded yet, unlocking the collateral
+		pub fn cancel_loan(origin, loan_id: LoanId) {
+			let sender = ensure_signed(origin)?;
+
+			let loan = Self::loan(loan_id).ok_or("Loan does not exist")?;
+			ensure!(loan.borrower == sender, "Only borrower can cancel loan");
+			ensure!(loan.funding.is_none(), "Loan is already funded");
+
+			T::Nft::unlock(loan.collateral)?;
+			<Loans<T>>::remove(loan_id);
+
+			Self::deposit_event(RawEvent::Cancelled(loan_id));
+		}
+
+		/// Fund a requested loan, paying the principal to the borrower
+		pub fn fund_loan(origin, loan_id: LoanId) {
+			let sender = ensure_signed(origin)?;
+
+			let mut loan = Self::loan(loan_id).ok_or("Loan does not exist")?;
+			ensure!(loan.funding.is_none(), "Loan is already funded");
+			ensure!(loan.borrower != sender, "Cannot fund your own loan");
+
+			T::Currency::transfer(&sender, &loan.borrower, loan.principal)?;
+
+			let deadline = <system::Module<T>>::block_number().saturating_add(loan.duration);
+			loan.funding = Some((sender.clone(), deadline));
+			<Loans<T>>::insert(loan_id, loan);
+
+			Self::deposit_event(RawEvent::Funded(loan_id, sender, deadline));
+		}
+
+		/// Pay back a funded loan before its deadline, unlocking the collateral
+		pub fn repay(origin, loan_id: LoanId) {
+			let sender = ensure_signed(origin)?;
+
+			let loan = Self::loan(loan_id).ok_or("Loan does not exist")?;
+			ensure!(loan.borrower == sender, "Only borrower can repay loan");
+			let (lender, deadline) = loan.funding.ok_or("Loan is not funded")?;
+			ensure!(<system::Module<T>>::block_number() <= deadline, "Loan is overdue");
+
+			// Keep the borrower alive, the collateral would be lost with a reaped account
+			let repayment = T::Currency::withdraw(&sender, loan.repayment, WithdrawReason::Transfer, ExistenceRequirement::KeepAlive)?;
+			T::Currency::resolve_creating(&lender, repayment);
+
+			T::Nft::unlock(loan.collateral)?;
+			<Loans<T>>::remove(loan_id);
+
+			Self::deposit_event(RawEvent::Repaid(loan_id));
+		}
+
+		/// Take the collateral of a loan that was not repaid before its deadline
+		pub fn claim_collateral(origin, loan_id: LoanId) {
+			let sender = ensure_signed(origin)?;
+
+			let loan = Self::loan(loan_id).ok_or("Loan does not exist")?;
+			let (lender, deadline) = loan.funding.ok_or("Loan is not funded")?;
+			ensure!(lender == sender, "Only lender can claim collateral");
+			ensure!(<system::Module<T>>::block_number() > deadline, "Loan is not overdue");
+
+			Self::seize_collateral(&loan.borrower, &lender, loan.collateral)?;
+			<Loans<T>>::remove(loan_id);
+
+			Self::deposit_event(RawEvent::Claimed(loan_id, lender));
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Unlock the collateral and move it to the lender, locking it again if the transfer fails
+	fn seize_collateral(borrower: &T::AccountId, lender: &T::AccountId, collateral: AssetIdOf<T>) -> result::Result<(), &'static str> {
+		T::Nft::unlock(collateral)?;
+
+		if let Err(e) = T::Nft::transfer(borrower, lender, collateral) {
+			T::Nft::lock(collateral)?;
+			return Err(e);
+		}
+
+		Ok(())
+	}
+}
+
+/// Tests for Loans module
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use runtime_io::with_externalities;
+	use primitives::{H256, Blake2Hasher};
+	use support::{impl_outer_origin, impl_outer_event, assert_ok, assert_noop, parameter_types};
+	use support::traits::{LockIdentifier, Get, OnFreeBalanceZero};
+	use sr_primitives::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+	use sr_primitives::weights::Weight;
+	use sr_primitives::Perbill;
+	use crate::kitties::{self, ReapPolicy};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	mod loans {
+		pub use crate::loans::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for Test {
+			balances<T>,
+			kitties<T>,
+			loans<T>,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq, Debug)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	}
+	impl system::Trait for Test {
+		type Origin = Origin;
+		type Call = ();
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type WeightMultiplierUpdate = ();
+		type Event = TestEvent;
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 1;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+		pub const TransactionBaseFee: u64 = 0;
+		pub const TransactionByteFee: u64 = 0;
+	}
+	impl balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = Kitties;
+		type OnNewAccount = ();
+		type Event = TestEvent;
+		type TransactionPayment = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+		type TransactionBaseFee = TransactionBaseFee;
+		type TransactionByteFee = TransactionByteFee;
+		type WeightToFee = ();
+	}
+	parameter_types! {
+		pub const KittyReapPolicy: ReapPolicy = ReapPolicy::Shelter;
+		pub const KittiesLockId: LockIdentifier = *b"kitties ";
+		pub const NameByteDeposit: u64 = 10;
+		pub const MaxNameLength: u32 = 8;
+		pub const MaxKittiesPerAccount: u32 = 10;
+		pub const MaxGen0Kitties: u32 = 100;
+		pub const BreedingFee: u64 = 0;
+		pub const HatchingPeriod: u64 = 1;
+		pub const KittenPeriod: u64 = 0;
+		pub const AdultPeriod: u64 = u64::max_value();
+		pub const Lifespan: Option<u64> = None;
+	}
+	impl kitties::Trait for Test {
+		type Event = TestEvent;
+		type KittyIndex = u32;
+		type Currency = Balances;
+		type KittyReapPolicy = KittyReapPolicy;
+		type ReapLockId = KittiesLockId;
+		type NameByteDeposit = NameByteDeposit;
+		type MaxNameLength = MaxNameLength;
+		type MaxKittiesPerAccount = MaxKittiesPerAccount;
+		type MaxGen0Kitties = MaxGen0Kitties;
+		type BreedingBaseFee = BreedingFee;
+		type BreedingFeePerKitty = BreedingFee;
+		type BreedingFeePerGeneration = BreedingFee;
+		type BreedingFeeDestination = ();
+		type HatchingPeriod = HatchingPeriod;
+		type KittenPeriod = KittenPeriod;
+		type AdultPeriod = AdultPeriod;
+		type Lifespan = Lifespan;
+		type OnKittyCreated = ();
+		type OnKittyTransferred = ();
+	}
+	impl Trait for Test {
+		type Event = TestEvent;
+		type Currency = Balances;
+		type Nft = Kitties;
+	}
+	type LoanModule = Module<Test>;
+	type Kitties = kitties::Module<Test>;
+	type System = system::Module<Test>;
+	type Balances = balances::Module<Test>;
+
+	// Account 1 owns kitties 0 and 1, accounts 1, 2 and 3 have 100 each
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		balances::GenesisConfig::<Test> {
+			balances: vec![(1, 100), (2, 100), (3, 100)],
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+
+		let mut ext: runtime_io::TestExternalities<Blake2Hasher> = t.into();
+		with_externalities(&mut ext, || {
+			System::set_block_number(1);
+			assert_ok!(Kitties::create(Origin::signed(1)));
+			assert_ok!(Kitties::create(Origin::signed(1)));
+		});
+		ext
+	}
+
+	fn last_event() -> TestEvent {
+		System::events().pop().expect("Event expected").event
+	}
+
+	#[test]
+	fn request_loan_locks_collateral() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(LoanModule::request_loan(Origin::signed(1), 0, 50, 60, 10));
+
+			assert_eq!(last_event(), TestEvent::loans(RawEvent::Requested(0, 1, 0, 50, 60)));
+			assert_eq!(LoanModule::loan(0), Some(Loan {
+				borrower: 1,
+				collateral: 0,
+				principal: 50,
+				repayment: 60,
+				duration: 10,
+				funding: None,
+			}));
+			assert_eq!(LoanModule::next_loan_id(), 1);
+			assert!(Kitties::is_locked(0));
+
+			// The collateral cannot be sold, bred or transferred
+			assert_noop!(Kitties::transfer(Origin::signed(1), 2, 0), "Kitty is locked");
+			assert_noop!(Kitties::ask(Origin::signed(1), 0, Some(10), None), "Kitty is locked");
+			assert_noop!(Kitties::breed(Origin::signed(1), 0, 1), "Kitty is locked");
+			assert_noop!(LoanModule::request_loan(Origin::signed(1), 0, 50, 60, 10), "Kitty is locked");
+		});
+	}
+
+	#[test]
+	fn request_loan_fails_for_invalid_terms() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(LoanModule::request_loan(Origin::signed(2), 0, 50, 60, 10), "Only owner can borrow against asset");
+			assert_noop!(LoanModule::request_loan(Origin::signed(1), 5, 50, 60, 10), "Only owner can borrow against asset");
+			assert_noop!(LoanModule::request_loan(Origin::signed(1), 0, 0, 60, 10), "Principal is zero");
+			assert_noop!(LoanModule::request_loan(Origin::signed(1), 0, 50, 40, 10), "Repayment is below principal");
+			assert_noop!(LoanModule::request_loan(Origin::signed(1), 0, 50, 60, 0), "Duration is zero");
+		});
+	}
+
+	#[test]
+	fn loan_request_can_be_cancelled() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(LoanModule::request_loan(Origin::signed(1), 0, 50, 60, 10));
+
+			assert_noop!(LoanModule::cancel_loan(Origin::signed(2), 0), "Only borrower can cancel loan");
+			assert_ok!(LoanModule::cancel_loan(Origin::signed(1), 0));
+
+			assert_eq!(last_event(), TestEvent::loans(RawEvent::Cancelled(0)));
+			assert_eq!(LoanModule::loan(0), None);
+			assert!(!Kitties::is_locked(0));
+			assert_noop!(LoanModule::fund_loan(Origin::signed(2), 0), "Loan does not exist");
+		});
+	}
+
+	#[test]
+	fn funded_loan_can_be_repaid() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(LoanModule::request_loan(Origin::signed(1), 0, 50, 60, 10));
+
+			assert_noop!(LoanModule::fund_loan(Origin::signed(1), 0), "Cannot fund your own loan");
+			assert_noop!(LoanModule::repay(Origin::signed(1), 0), "Loan is not funded");
+			assert_ok!(LoanModule::fund_loan(Origin::signed(2), 0));
+
+			assert_eq!(last_event(), TestEvent::loans(RawEvent::Funded(0, 2, 11)));
+			assert_eq!(Balances::free_balance(&1), 150);
+			assert_eq!(Balances::free_balance(&2), 50);
+			assert_noop!(LoanModule::fund_loan(Origin::signed(3), 0), "Loan is already funded");
+			assert_noop!(LoanModule::cancel_loan(Origin::signed(1), 0), "Loan is already funded");
+			assert_noop!(LoanModule::claim_collateral(Origin::signed(2), 0), "Loan is not overdue");
+
+			System::set_block_number(11);
+			assert_noop!(LoanModule::repay(Origin::signed(2), 0), "Only borrower can repay loan");
+			assert_ok!(LoanModule::repay(Origin::signed(1), 0));
+
+			assert_eq!(last_event(), TestEvent::loans(RawEvent::Repaid(0)));
+			assert_eq!(Balances::free_balance(&1), 90);
+			assert_eq!(Balances::free_balance(&2), 110);
+			assert_eq!(LoanModule::loan(0), None);
+			assert!(!Kitties::is_locked(0));
+			assert_eq!(Kitties::kitty_owner(0), Some(1));
+		});
+	}
+
+	#[test]
+	fn lender_claims_collateral_of_defaulted_loan() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(LoanModule::request_loan(Origin::signed(1), 0, 50, 60, 10));
+			assert_ok!(LoanModule::fund_loan(Origin::signed(2), 0));
+
+			System::set_block_number(12);
+			assert_noop!(LoanModule::repay(Origin::signed(1), 0), "Loan is overdue");
+			assert_noop!(LoanModule::claim_collateral(Origin::signed(3), 0), "Only lender can claim collateral");
+			assert_ok!(LoanModule::claim_collateral(Origin::signed(2), 0));
+
+			assert_eq!(last_event(), TestEvent::loans(RawEvent::Claimed(0, 2)));
+			assert_eq!(LoanModule::loan(0), None);
+			assert_eq!(Kitties::kitty_owner(0), Some(2));
+			assert!(!Kitties::is_locked(0));
+			assert_eq!(Balances::free_balance(&1), 150);
+			assert_eq!(Balances::free_balance(&2), 50);
+			assert_eq!(Kitties::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn collateral_of_reaped_borrower_stays_claimable() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(LoanModule::request_loan(Origin::signed(1), 0, 50, 60, 10));
+			assert_ok!(LoanModule::fund_loan(Origin::signed(2), 0));
+
+			<Kitties as OnFreeBalanceZero<u64>>::on_free_balance_zero(&1);
+
+			// The collateral stays with the borrower, the other kitty goes to the shelter
+			assert_eq!(Kitties::kitty_owner(0), Some(1));
+			assert_eq!(Kitties::kitty_owner(1), None);
+
+			System::set_block_number(12);
+			assert_ok!(LoanModule::claim_collateral(Origin::signed(2), 0));
+			assert_eq!(Kitties::kitty_owner(0), Some(2));
+			assert_eq!(Kitties::check_integrity(), vec![]);
+		});
+	}
+
+	#[test]
+	fn repay_keeps_the_borrower_alive() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(LoanModule::request_loan(Origin::signed(1), 0, 50, 150, 10));
+			assert_ok!(LoanModule::fund_loan(Origin::signed(2), 0));
+
+			// The collateral would go back to a reaped account
+			assert_noop!(LoanModule::repay(Origin::signed(1), 0), "payment would kill account");
+
+			assert_ok!(Balances::transfer(Origin::signed(3), 1, 1));
+			assert_ok!(LoanModule::repay(Origin::signed(1), 0));
+			assert_eq!(Balances::free_balance(&1), 1);
+			assert_eq!(Balances::free_balance(&2), 200);
+			assert!(!Kitties::is_locked(0));
+		});
+	}
+
+	#[test]
+	fn failed_claim_keeps_collateral_locked() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(LoanModule::request_loan(Origin::signed(1), 0, 50, 60, 10));
+			assert_ok!(LoanModule::fund_loan(Origin::signed(2), 0));
+			// The lender cannot take one more kitty
+			for _ in 0..MaxKittiesPerAccount::get() {
+				assert_ok!(Kitties::create(Origin::signed(2)));
+			}
+
+			System::set_block_number(12);
+			assert_eq!(LoanModule::claim_collateral(Origin::signed(2), 0), Err("Account has too many kitties"));
+			assert!(LoanModule::loan(0).is_some());
+			assert!(Kitties::is_locked(0));
+			assert_eq!(Kitties::kitty_owner(0), Some(1));
+		});
+	}
+}
@@ -0,0 +1,47 @@
+//! Non-fungible assets shared between modules
+
+use support::Parameter;
+use sr_primitives::traits::Member;
+use rstd::result;
+
+/// A collection of unique assets, each owned by one account.
+///
+/// Modules that build on assets (loans, auctions, games) take a `NonFungibleAsset` instead of
+/// reaching into the storage of the module that keeps them.
+pub trait NonFungibleAsset<AccountId> {
+	/// Identifies an asset of the collection
+	type AssetId: Parameter + Member + Copy;
+
+	/// Whether the asset exists
+	fn exists(asset_id: Self::AssetId) -> bool;
+
+	/// The owner of the asset. `None` if it does not exist or has no owner.
+	fn owner_of(asset_id: Self::AssetId) -> Option<AccountId>;
+
+	/// Number of assets in the collection
+	fn total_supply() -> u64;
+
+	/// Move the asset from `from` to `to`. Fails if `from` is not the owner or the asset is locked.
+	fn transfer(from: &AccountId, to: &AccountId, asset_id: Self::AssetId) -> result::Result<(), &'static str>;
+
+	/// Lock the asset so it cannot be transferred, sold or otherwise used until it is unlocked
+	fn lock(asset_id: Self::AssetId) -> result::Result<(), &'static str>;
+
+	/// Unlock an asset locked by `lock`
+	fn unlock(asset_id: Self::AssetId) -> result::Result<(), &'static str>;
+
+	/// Whether the asset is locked
+	fn is_locked(asset_id: Self::AssetId) -> bool;
+
+	/// Whether `who` can take one more asset
+	fn can_receive(who: &AccountId) -> bool;
+}
+
+/// Assets carrying a DNA that games can read traits from
+pub trait AssetDna<AccountId>: NonFungibleAsset<AccountId> {
+	/// The DNA of the asset. `None` if it does not exist.
+	fn dna(asset_id: Self::AssetId) -> Option<[u8; 16]>;
+
+	/// Whether the asset can take part in games now. `false` if it does not exist.
+	fn can_play(asset_id: Self::AssetId) -> bool;
+}